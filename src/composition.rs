@@ -1,6 +1,11 @@
-use super::keyboards::{KeyCode, KeyEvents, Keyboard, MetaKey};
-use super::transformers::{Transformable, TransformerTypes};
-use crate::{tf, CompositionResult, Context};
+use super::keyboards::{
+  KeyCode, KeyCodeSpace, KeyEvents, Keyboard, Keyboards, MetaKey, SimultaneousDetector, Stroke,
+  TimedKeyEvent,
+};
+use super::transformers::{
+  is_nicola_key, nicola_lookup, InputMethod, Transformable, TransformerTypes,
+};
+use crate::{set, tf, CompositionResult, Context};
 
 #[derive(Clone)]
 pub struct Composition {
@@ -8,6 +13,12 @@ pub struct Composition {
   base_transformer_type: TransformerTypes,
   context: Context,
   keyboard: Box<dyn Keyboard>,
+  // keyboardが受け取るキーコードの種類
+  keyboard_space: KeyCodeSpace,
+  simultaneous: Option<SimultaneousDetector>,
+  // スティッキーシフトで、次のキーをシフトを押したものとして扱うかどうか
+  is_sticky_shift: bool,
+  generation: u64,
   // TODO: 変更のあった辞書要素を保持できる必要あり？
   // 変更は読みと変換先だけあればいいかな。
   // 読みがマッチした要素の候補の先頭に候補を挿入する
@@ -16,20 +27,32 @@ pub struct Composition {
 
 impl Composition {
   pub fn new(context: Context, transformer_types: TransformerTypes) -> Self {
-    let keyboard = context.config().keyboard_type.to_keyboard();
+    let keyboard = context.config().to_keyboard();
+    let keyboard_space = context.config().key_code_space;
+    let simultaneous = match context.config().input_method {
+      InputMethod::Nicola => Some(SimultaneousDetector::new(
+        context.config().thumb_shift.clone(),
+      )),
+      _ => None,
+    };
 
     Composition {
       transformer: tf!(context.clone(), transformer_types),
       base_transformer_type: transformer_types,
       context,
       keyboard,
+      keyboard_space,
+      simultaneous,
+      is_sticky_shift: false,
+      generation: 0,
     }
   }
 
   #[cfg(test)]
   pub fn new_from_transformer(context: Context, transformer: Box<dyn Transformable>) -> Self {
     let context = context.new_empty();
-    let keyboard = context.config().keyboard_type.to_keyboard();
+    let keyboard = context.config().to_keyboard();
+    let keyboard_space = context.config().key_code_space;
     let mut tf = transformer;
     tf.set_context(context.clone());
 
@@ -38,6 +61,10 @@ impl Composition {
       base_transformer_type: TransformerTypes::Direct,
       context,
       keyboard,
+      keyboard_space,
+      simultaneous: None,
+      is_sticky_shift: false,
+      generation: 0,
     }
   }
 
@@ -49,15 +76,71 @@ impl Composition {
     self.transformer.is_empty()
   }
 
+  pub fn is_private_mode(&self) -> bool {
+    self.context.is_private_mode()
+  }
+
+  pub fn set_private_mode(&mut self, is_private_mode: bool) {
+    self.context.set_private_mode(is_private_mode);
+  }
+
+  pub fn key_code_space(&self) -> KeyCodeSpace {
+    self.context.config().key_code_space
+  }
+
+  pub fn keyboard_type(&self) -> Keyboards {
+    self.context.config().keyboard_type
+  }
+
+  pub fn generation(&self) -> u64 {
+    self.generation
+  }
+
+  pub fn set_generation(&mut self, generation: u64) {
+    self.generation = generation;
+  }
+
+  // 設定と違う種類のキーコードが届いたときは、その種類に合わせたキーボードに切り替える
+  pub fn push_key_event_as(&mut self, space: KeyCodeSpace, event: &KeyEvents) -> bool {
+    if space != self.keyboard_space {
+      self.keyboard = self.context.config().to_keyboard_for(space);
+      self.keyboard_space = space;
+    }
+
+    self.push_key_event(event)
+  }
+
   pub fn push_key_event(&mut self, event: &KeyEvents) -> bool {
+    if let Some(is_processed) = self.try_sticky_shift(event) {
+      return is_processed;
+    }
+    if self.take_sticky_shift(event) && !self.keyboard.is_pressing_shift() {
+      let shift = KeyCode::Meta(MetaKey::Shift);
+      self.keyboard.key_down(&shift);
+      let is_processed = self.process_key_event(event);
+      self.keyboard.key_up(&shift);
+
+      return is_processed;
+    }
+
+    self.process_key_event(event)
+  }
+
+  fn process_key_event(&mut self, event: &KeyEvents) -> bool {
     self.keyboard.push_event(event);
 
+    if self.try_toggle_private_mode(event) {
+      return true;
+    }
+
     KeyEventProcessor::new(event, &self.keyboard, &self.transformer)
       .next()
       .map(|result| match result {
         KeyEventProcessorResult::KeyProcessed(new_tf) => self.transformer = new_tf,
         KeyEventProcessorResult::TransformerChanged(new_tf) => {
-          self.base_transformer_type = new_tf.transformer_type();
+          if new_tf.is_base_transformer() {
+            self.base_transformer_type = new_tf.transformer_type();
+          }
           self.transformer = new_tf;
         }
       })
@@ -65,8 +148,203 @@ impl Composition {
       .unwrap_or(false)
   }
 
-  pub fn next_composition(&self) -> Composition {
-    Composition::new(self.context.new_empty(), self.base_transformer_type)
+  // 親指シフトでは、同時打鍵を判定してから文字を入力する
+  // 判定待ちのキーは入力元に返せないため、イベントは処理したものとして扱う
+  pub fn push_timed_key_event(&mut self, event: &TimedKeyEvent) -> bool {
+    if self.simultaneous.is_none() {
+      return self.push_key_event(&event.event);
+    }
+
+    let is_target = self.is_simultaneous_mode()
+      && !self.keyboard.is_combination()
+      && !self.keyboard.is_pressing_shift()
+      && match &event.event {
+        KeyEvents::KeyDown(key) | KeyEvents::KeyUp(key) | KeyEvents::KeyRepeat(key) => {
+          self.is_simultaneous_key(key)
+        }
+        KeyEvents::Character(_) => false,
+      };
+    let strokes = self
+      .simultaneous
+      .as_mut()
+      .map(|detector| match is_target {
+        true => detector.push(event),
+        false => detector.flush(),
+      })
+      .unwrap_or_default();
+    strokes
+      .into_iter()
+      .for_each(|stroke| self.push_stroke(stroke));
+
+    match is_target || self.is_stopped() {
+      true => is_target,
+      false => self.push_key_event(&event.event),
+    }
+  }
+
+  // 判定時間を過ぎても次のキーが押されない場合に、判定待ちのキーを確定する
+  pub fn flush_expired_keys(&mut self, now: u64) -> bool {
+    let strokes = self
+      .simultaneous
+      .as_mut()
+      .map(|detector| detector.flush_expired(now))
+      .unwrap_or_default();
+    let is_flushed = !strokes.is_empty();
+    strokes
+      .into_iter()
+      .for_each(|stroke| self.push_stroke(stroke));
+
+    is_flushed
+  }
+
+  // 英数などでは親指キーをスペースとして使うため、かなを入力するモードでだけ判定する
+  fn is_simultaneous_mode(&self) -> bool {
+    match self.transformer.transformer_type() {
+      TransformerTypes::Hiragana | TransformerTypes::Katakana | TransformerTypes::Henkan => true,
+      _ => false,
+    }
+  }
+
+  fn is_simultaneous_key(&self, key: &KeyCode) -> bool {
+    self
+      .simultaneous
+      .as_ref()
+      .map_or(false, |detector| detector.is_thumb(key))
+      || match key {
+        KeyCode::Printable(c) => is_nicola_key(*c),
+        _ => false,
+      }
+  }
+
+  fn push_stroke(&mut self, stroke: Stroke) {
+    match stroke {
+      Stroke::Shifted(key, thumb) => {
+        match key
+          .printable_key()
+          .and_then(|c| nicola_lookup(c, Some(thumb)))
+        {
+          Some(kana) => kana.chars().for_each(|c| {
+            if let Some(tf) = self.transformer.push_key(&KeyCode::Printable(c)) {
+              self.transformer = tf;
+            }
+          }),
+          None => self.push_stroke(Stroke::Single(key)),
+        }
+      }
+      Stroke::Single(key) => {
+        let is_processed = self.push_key_event(&KeyEvents::KeyDown(key));
+        self.push_key_event(&KeyEvents::KeyUp(key));
+        // 処理されなかったスペースなどは、そのまま出力する
+        match key.printable_key() {
+          Some(c) if !is_processed && self.transformer.is_base_transformer() => {
+            self.transformer = self
+              .transformer
+              .to_completed_with_update_buffer(c.to_string())
+          }
+          _ => {}
+        }
+      }
+    }
+  }
+
+  // sticky_keyを押すと次のキーをシフトを押したものとして扱い、続けて押すとその文字を入力する
+  // AZIKの「;」のようにローマ字のルールで使われているキーでも、スティッキーシフトを優先する
+  // かな入力でかなが割り当てられているキーは、かなの入力を優先する
+  fn try_sticky_shift(&mut self, event: &KeyEvents) -> Option<bool> {
+    let config = self.context.config();
+    if !config.is_enable_sticky_shift || self.keyboard.is_combination() {
+      return None;
+    }
+
+    let key = match event {
+      KeyEvents::KeyDown(key) | KeyEvents::KeyUp(key) => *key,
+      _ => return None,
+    };
+    let character = key.printable_key()?;
+    if !config.key_config().sticky_key.fulfilled(&set![key])
+      || self.context.rule_table().is_mapped(character)
+    {
+      return None;
+    }
+
+    match event {
+      // シフトと一緒に押したsticky_keyはキーボードに渡しているので、離したときも渡す
+      KeyEvents::KeyUp(_) if self.keyboard.is_pressing(&key) => None,
+      KeyEvents::KeyUp(_) => Some(true),
+      _ if self.keyboard.is_pressing_shift() => None,
+      _ if self.is_sticky_shift => {
+        self.is_sticky_shift = false;
+        Some(self.push_literal(character))
+      }
+      _ => {
+        self.is_sticky_shift = true;
+        Some(true)
+      }
+    }
+  }
+
+  // 修飾キー以外のキーを押したら、スティッキーシフトを解除する。文字のキーならシフトを付ける
+  fn take_sticky_shift(&mut self, event: &KeyEvents) -> bool {
+    let key = match event {
+      KeyEvents::KeyDown(key) if self.is_sticky_shift => key,
+      _ => return false,
+    };
+    match key {
+      KeyCode::Meta(MetaKey::Shift)
+      | KeyCode::Meta(MetaKey::Ctrl)
+      | KeyCode::Meta(MetaKey::Alt)
+      | KeyCode::Meta(MetaKey::Super) => false,
+      _ => {
+        self.is_sticky_shift = false;
+        key.is_printable()
+      }
+    }
+  }
+
+  // 入力途中のローマ字があっても、その文字をそのまま出力する
+  fn push_literal(&mut self, character: char) -> bool {
+    match self.transformer.is_base_transformer() {
+      true => {
+        self.transformer = self.transformer.to_completed_with_literal(character);
+        true
+      }
+      false => match self.transformer.push_key(&KeyCode::Printable(character)) {
+        Some(tf) => {
+          self.transformer = tf;
+          true
+        }
+        None => false,
+      },
+    }
+  }
+
+  fn try_toggle_private_mode(&mut self, event: &KeyEvents) -> bool {
+    let is_toggle = match event {
+      KeyEvents::KeyDown(_) => self
+        .context
+        .config()
+        .key_config()
+        .toggle_private_mode
+        .fulfilled(self.keyboard.pressing_keys()),
+      _ => false,
+    };
+    if is_toggle {
+      self.set_private_mode(!self.is_private_mode());
+    }
+
+    is_toggle
+  }
+
+  pub fn next_composition_with(&self, context: Context, generation: u64) -> Composition {
+    let mut composition = Composition::new(context, self.base_transformer_type);
+    composition.is_sticky_shift = self.is_sticky_shift;
+    // 判定待ちのキーは次の入力に引き継ぐ
+    if composition.simultaneous.is_some() {
+      composition.simultaneous = self.simultaneous.clone();
+    }
+    composition.set_generation(generation);
+
+    composition
   }
 
   pub fn stopped_buffer(&self) -> String {
@@ -82,6 +360,10 @@ impl Composition {
     self.transformer.display_string()
   }
 
+  pub fn cursor_offset(&self) -> usize {
+    self.transformer.cursor_offset()
+  }
+
   pub fn transformer_type(&self) -> TransformerTypes {
     self.transformer.transformer_type()
   }
@@ -98,6 +380,7 @@ impl Composition {
     self.base_transformer_type
   }
 
+  // プライベートモードで確定した語は、確定したときに辞書の更新から外している
   pub fn result(&self) -> CompositionResult {
     self.transformer.context().result().clone()
   }
@@ -134,6 +417,20 @@ impl<'a> KeyEventProcessor<'a> {
   }
 
   pub fn next(&self) -> Option<KeyEventProcessorResult> {
+    // 確定済みの文字はモードの切り替えに使わず、そのまま変換器に渡す
+    if let KeyEvents::Character(character) = self.event {
+      return self
+        .transformer
+        .push_key(&KeyCode::Printable(*character))
+        .map(|tf| KeyEventProcessorResult::KeyProcessed(tf));
+    }
+
+    if let Some(key) = self.action_key() {
+      return Self::is_process_key(&key, self.transformer)
+        .and_then(|_| self.transformer.push_key(&key))
+        .map(|tf| KeyEventProcessorResult::KeyProcessed(tf));
+    }
+
     Self::next_key_code(self.event)
       .and_then(|key| Self::is_process_key(key, self.transformer))
       .and(Some((
@@ -142,8 +439,7 @@ impl<'a> KeyEventProcessor<'a> {
       )))
       .map(|(raw, printable)| {
         self
-          .transformer
-          .try_change_transformer(&self.keyboard, &raw?)
+          .try_change_transformer(&raw?)
           .and_then(|tf| Some(KeyEventProcessorResult::TransformerChanged(tf)))
           .or_else(|| {
             self
@@ -155,9 +451,29 @@ impl<'a> KeyEventProcessor<'a> {
       .unwrap_or(None)
   }
 
+  // ctrlなどとの組み合わせは文字を入力しないため、設定された操作のキーとして扱う
+  fn action_key(&self) -> Option<KeyCode> {
+    Self::next_key_code(self.event)?;
+    match self.keyboard.is_combination() {
+      true => self.transformer.context().config().key_config().try_action(
+        self.keyboard.pressing_keys(),
+        self.transformer.is_selecting_candidate(),
+      ),
+      false => None,
+    }
+  }
+
+  // キーリピートではモードを切り替えず、入力だけを繰り返す
+  fn try_change_transformer(&self, raw: &KeyCode) -> Option<Box<dyn Transformable>> {
+    match self.event {
+      KeyEvents::KeyRepeat(_) => None,
+      _ => self.transformer.try_change_transformer(&self.keyboard, raw),
+    }
+  }
+
   fn next_key_code(event: &KeyEvents) -> Option<&KeyCode> {
     match event {
-      KeyEvents::KeyDown(key) => Some(key),
+      KeyEvents::KeyDown(key) | KeyEvents::KeyRepeat(key) => Some(key),
       _ => None,
     }
   }
@@ -171,7 +487,17 @@ impl<'a> KeyEventProcessor<'a> {
         KeyCode::Meta(MetaKey::ArrowDown) if transformer.is_base_transformer() => None,
         KeyCode::Meta(MetaKey::ArrowLeft) if transformer.is_base_transformer() => None,
         KeyCode::Meta(MetaKey::ArrowUp) if transformer.is_base_transformer() => None,
-        KeyCode::PrintableMeta(MetaKey::Space, _) if transformer.is_base_transformer() => None,
+        KeyCode::Meta(MetaKey::Home) if transformer.is_base_transformer() => None,
+        KeyCode::Meta(MetaKey::End) if transformer.is_base_transformer() => None,
+        KeyCode::Meta(MetaKey::PageUp) if transformer.is_base_transformer() => None,
+        KeyCode::Meta(MetaKey::PageDown) if transformer.is_base_transformer() => None,
+        // 全角英数では全角スペースを入力する
+        KeyCode::PrintableMeta(MetaKey::Space, _)
+          if transformer.is_base_transformer()
+            && transformer.transformer_type() != TransformerTypes::EmEisu =>
+        {
+          None
+        }
         KeyCode::PrintableMeta(MetaKey::Enter, _) if transformer.is_base_transformer() => None,
         KeyCode::PrintableMeta(MetaKey::Tab, _) if transformer.is_base_transformer() => None,
         KeyCode::PrintableMeta(MetaKey::Shift, _) if transformer.is_base_transformer() => None,
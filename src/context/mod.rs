@@ -1,27 +1,95 @@
 use std::fmt;
-use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
 
-use crate::transformers::Transformable;
+use crate::dictionary::{Candidate, CandidateStatistics, DynamicCandidateProviders};
+use crate::transformers::{RuleTable, Transformable};
 use crate::{CompositionResult, Dictionary, DictionaryEntry, RSKKConfig};
 
 #[derive(Clone)]
 pub struct Context {
-  config: Rc<RSKKConfig>,
-  dictionary: Rc<Dictionary>,
+  config: Arc<RSKKConfig>,
+  dictionary: Arc<Dictionary>,
+  // 確定のたびに更新するため、辞書とは別にロックして共有する
+  statistics: Arc<RwLock<CandidateStatistics>>,
+  providers: Arc<DynamicCandidateProviders>,
+  rule_table: Arc<RuleTable>,
+  // RSKKの設定とキー操作のどちらから切り替えても同じ値を見るよう共有する
+  is_private_mode: Arc<AtomicBool>,
   result: CompositionResult,
 }
 
 impl Context {
-  pub fn new(config: Rc<RSKKConfig>, dictionary: Rc<Dictionary>) -> Self {
+  pub fn new(config: Arc<RSKKConfig>, dictionary: Arc<Dictionary>) -> Self {
+    Self::new_with_providers(
+      config,
+      dictionary,
+      Arc::new(DynamicCandidateProviders::default()),
+    )
+  }
+
+  pub fn new_with_providers(
+    config: Arc<RSKKConfig>,
+    dictionary: Arc<Dictionary>,
+    providers: Arc<DynamicCandidateProviders>,
+  ) -> Self {
+    let rule_table = RuleTable::from_config(&config);
+
+    Self::new_with_rule_table(
+      config,
+      dictionary,
+      Arc::new(RwLock::new(CandidateStatistics::new())),
+      providers,
+      rule_table,
+    )
+  }
+
+  pub fn new_with_rule_table(
+    config: Arc<RSKKConfig>,
+    dictionary: Arc<Dictionary>,
+    statistics: Arc<RwLock<CandidateStatistics>>,
+    providers: Arc<DynamicCandidateProviders>,
+    rule_table: Arc<RuleTable>,
+  ) -> Self {
     Self {
       config,
       dictionary,
+      statistics,
+      providers,
+      rule_table,
+      is_private_mode: Arc::new(AtomicBool::new(false)),
       result: CompositionResult::new(),
     }
   }
 
   pub fn new_empty(&self) -> Self {
-    Self::new(self.config.clone(), self.dictionary.clone())
+    Self {
+      is_private_mode: self.is_private_mode.clone(),
+      ..Self::new_with_rule_table(
+        self.config.clone(),
+        self.dictionary.clone(),
+        self.statistics.clone(),
+        self.providers.clone(),
+        self.rule_table.clone(),
+      )
+    }
+  }
+
+  pub fn with_private_mode(self, is_private_mode: Arc<AtomicBool>) -> Self {
+    Self {
+      is_private_mode,
+      ..self
+    }
+  }
+
+  pub fn is_private_mode(&self) -> bool {
+    self.is_private_mode.load(Ordering::SeqCst)
+  }
+
+  pub fn set_private_mode(&self, is_private_mode: bool) {
+    self
+      .is_private_mode
+      .store(is_private_mode, Ordering::SeqCst);
   }
 
   pub fn config(&self) -> &RSKKConfig {
@@ -32,6 +100,47 @@ impl Context {
     &self.dictionary
   }
 
+  pub fn statistics(&self) -> Arc<RwLock<CandidateStatistics>> {
+    self.statistics.clone()
+  }
+
+  pub fn rank_candidates(&self, entry: &DictionaryEntry) -> Vec<Candidate> {
+    self
+      .statistics
+      .read()
+      .unwrap_or_else(|e| e.into_inner())
+      .rank(
+        &entry.read,
+        &entry.candidates,
+        self.config.candidate_ranking,
+      )
+  }
+
+  pub fn rule_table(&self) -> Arc<RuleTable> {
+    self.rule_table.clone()
+  }
+
+  pub fn dynamic_candidates(&self, read: &str) -> Vec<Candidate> {
+    self.providers.candidates(read, &self.config)
+  }
+
+  // 辞書の候補の後ろに動的に生成した候補を並べる
+  // 生成した候補は学習しないよう、別に返す
+  pub fn lookup(&self, read: &str) -> Option<(DictionaryEntry, Vec<Candidate>)> {
+    let mut candidates = self
+      .dictionary
+      .transform(read)
+      .map(|entry| entry.candidates.clone())
+      .unwrap_or(vec![]);
+    let dynamic_candidates = self.dynamic_candidates(read);
+    candidates.append(&mut dynamic_candidates.clone());
+
+    match candidates.is_empty() {
+      true => None,
+      false => Some((DictionaryEntry::new(read, candidates), dynamic_candidates)),
+    }
+  }
+
   pub fn result(&self) -> &CompositionResult {
     &self.result
   }
@@ -1,10 +1,16 @@
 mod candidate;
 mod dictionary_entry;
+mod providers;
+mod statistics;
 
 use std::collections::HashSet;
 
 pub use candidate::Candidate;
 pub use dictionary_entry::DictionaryEntry;
+pub use providers::{DateTimeConfig, DynamicCandidateProvider, DynamicCandidateProviders};
+#[cfg(test)]
+pub use providers::{DateTimeProvider, FixedClock};
+pub use statistics::{CandidateStatistics, RankingPolicy};
 
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct Dictionary {
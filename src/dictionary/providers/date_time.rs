@@ -0,0 +1,261 @@
+use super::{Clock, DynamicCandidateProvider};
+use crate::dictionary::Candidate;
+use crate::RSKKConfig;
+
+// 書式は strftime のサブセットに和暦と曜日を加えたもの
+//   %Y 年, %m 月(2桁), %-m 月, %d 日(2桁), %-d 日, %H 時, %M 分, %S 秒
+//   %E 元号, %e 元号での年(最初の年は「元」), %-e 元号での年(数字), %a 曜日(日月火水木金土), %% %そのもの
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DateTimeConfig {
+  pub today: Vec<String>,
+  pub now: Vec<String>,
+  // UTCからの時差(分)。ホストのタイムゾーンに合わせて設定する。省略時は日本標準時(UTC+9)
+  pub utc_offset_minutes: i64,
+}
+
+impl Default for DateTimeConfig {
+  fn default() -> Self {
+    DateTimeConfig {
+      today: vec![
+        "%Y-%m-%d".to_owned(),
+        "%Y年%-m月%-d日".to_owned(),
+        "%E%e年%-m月%-d日".to_owned(),
+        "%Y/%m/%d(%a)".to_owned(),
+      ],
+      now: vec![
+        "%H:%M".to_owned(),
+        "%H時%M分".to_owned(),
+        "%Y-%m-%d %H:%M:%S".to_owned(),
+      ],
+      utc_offset_minutes: 9 * 60,
+    }
+  }
+}
+
+const ERAS: [(&str, (i64, u32, u32)); 5] = [
+  ("令和", (2019, 5, 1)),
+  ("平成", (1989, 1, 8)),
+  ("昭和", (1926, 12, 25)),
+  ("大正", (1912, 7, 30)),
+  ("明治", (1868, 1, 25)),
+];
+
+const WEEKDAYS: [&str; 7] = ["日", "月", "火", "水", "木", "金", "土"];
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct DateTime {
+  pub year: i64,
+  pub month: u32,
+  pub day: u32,
+  pub hour: u32,
+  pub minute: u32,
+  pub second: u32,
+  // 0が日曜日
+  pub weekday: u32,
+}
+
+impl DateTime {
+  pub fn from_unix(timestamp: i64, utc_offset_minutes: i64) -> Self {
+    let seconds = timestamp + utc_offset_minutes * 60;
+    let days = seconds.div_euclid(86400);
+    let rest = seconds.rem_euclid(86400);
+    let (year, month, day) = civil_from_days(days);
+
+    DateTime {
+      year,
+      month,
+      day,
+      hour: (rest / 3600) as u32,
+      minute: (rest % 3600 / 60) as u32,
+      second: (rest % 60) as u32,
+      weekday: (days + 4).rem_euclid(7) as u32,
+    }
+  }
+
+  pub fn era(&self) -> Option<(&'static str, i64)> {
+    ERAS
+      .iter()
+      .find(|(_, start)| (self.year, self.month, self.day) >= *start)
+      .map(|(name, (year, _, _))| (*name, self.year - year + 1))
+  }
+
+  // 元号のない日付は西暦の年にする
+  fn era_year(&self) -> i64 {
+    self.era().map(|(_, year)| year).unwrap_or(self.year)
+  }
+
+  pub fn format(&self, format: &str) -> String {
+    let mut ret = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+      if c != '%' {
+        ret.push(c);
+        continue;
+      }
+
+      match chars.next() {
+        Some('Y') => ret += &self.year.to_string(),
+        Some('m') => ret += &format!("{:02}", self.month),
+        Some('d') => ret += &format!("{:02}", self.day),
+        Some('H') => ret += &format!("{:02}", self.hour),
+        Some('M') => ret += &format!("{:02}", self.minute),
+        Some('S') => ret += &format!("{:02}", self.second),
+        Some('E') => ret += self.era().map(|(name, _)| name).unwrap_or(""),
+        Some('e') => match self.era() {
+          Some((_, 1)) => ret += "元",
+          _ => ret += &self.era_year().to_string(),
+        },
+        Some('a') => ret += WEEKDAYS[self.weekday as usize],
+        Some('%') => ret.push('%'),
+        Some('-') => match chars.next() {
+          Some('m') => ret += &self.month.to_string(),
+          Some('d') => ret += &self.day.to_string(),
+          Some('H') => ret += &self.hour.to_string(),
+          Some('e') => ret += &self.era_year().to_string(),
+          Some(c) => ret += &format!("%-{}", c),
+          None => ret += "%-",
+        },
+        Some(c) => ret += &format!("%{}", c),
+        None => ret.push('%'),
+      }
+    }
+
+    ret
+  }
+}
+
+// 1970-01-01からの日数をグレゴリオ暦の年月日に変換する
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+  let z = days + 719468;
+  let era = z.div_euclid(146097);
+  let doe = z.rem_euclid(146097);
+  let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+  let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+  (year, month, day)
+}
+
+pub struct DateTimeProvider {
+  clock: Box<dyn Clock>,
+}
+
+impl DateTimeProvider {
+  pub fn new(clock: Box<dyn Clock>) -> Self {
+    DateTimeProvider { clock }
+  }
+}
+
+impl DynamicCandidateProvider for DateTimeProvider {
+  fn candidates(&self, read: &str, config: &RSKKConfig) -> Vec<Candidate> {
+    let config = &config.date_time;
+    let formats = match read {
+      "today" => &config.today,
+      "now" => &config.now,
+      _ => return vec![],
+    };
+    let now = DateTime::from_unix(self.clock.now(), config.utc_offset_minutes);
+
+    formats
+      .iter()
+      .map(|format| Candidate::new(now.format(format), None))
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::FixedClock;
+  use super::*;
+
+  // 2026-10-17 13:05:09 JST
+  const TIMESTAMP: i64 = 1792209909;
+
+  #[test]
+  fn from_unix() {
+    let date = DateTime::from_unix(TIMESTAMP, 9 * 60);
+    assert_eq!(
+      date,
+      DateTime {
+        year: 2026,
+        month: 10,
+        day: 17,
+        hour: 13,
+        minute: 5,
+        second: 9,
+        weekday: 6,
+      }
+    );
+
+    let date = DateTime::from_unix(TIMESTAMP, -14 * 60);
+    assert_eq!((date.month, date.day, date.hour), (10, 16, 14));
+
+    let date = DateTime::from_unix(951782400, 0);
+    assert_eq!((date.year, date.month, date.day), (2000, 2, 29));
+  }
+
+  #[test]
+  fn era() {
+    let era = |year, month, day| {
+      DateTime {
+        year,
+        month,
+        day,
+        hour: 0,
+        minute: 0,
+        second: 0,
+        weekday: 0,
+      }
+      .era()
+    };
+
+    assert_eq!(era(2026, 10, 17), Some(("令和", 8)));
+    assert_eq!(era(2019, 5, 1), Some(("令和", 1)));
+    assert_eq!(era(2019, 4, 30), Some(("平成", 31)));
+    assert_eq!(era(1989, 1, 7), Some(("昭和", 64)));
+    assert_eq!(era(1926, 12, 24), Some(("大正", 15)));
+    assert_eq!(era(1868, 1, 1), None);
+  }
+
+  #[test]
+  fn format() {
+    let date = DateTime::from_unix(TIMESTAMP, 9 * 60);
+    assert_eq!(date.format("%Y-%m-%d"), "2026-10-17");
+    assert_eq!(date.format("%Y年%-m月%-d日"), "2026年10月17日");
+    assert_eq!(date.format("%E%e年%-m月%-d日(%a)"), "令和8年10月17日(土)");
+    assert_eq!(date.format("%H:%M:%S %-H時"), "13:05:09 13時");
+    assert_eq!(date.format("100%% %q %"), "100% %q %");
+
+    // 元号の最初の年は「元年」と書く
+    let date = DateTime::from_unix(1556668800, 9 * 60);
+    assert_eq!(date.format("%E%e年%-m月%-d日"), "令和元年5月1日");
+    assert_eq!(date.format("%E%-e年"), "令和1年");
+    let date = DateTime::from_unix(-3786825600, 9 * 60);
+    assert_eq!(date.format("%E%e年"), "1850年");
+  }
+
+  #[test]
+  fn candidates() {
+    let provider = DateTimeProvider::new(box FixedClock(TIMESTAMP));
+    let mut config = RSKKConfig::default_config();
+    config.date_time.today = vec!["%Y-%m-%d".to_owned(), "%E%e年".to_owned()];
+    config.date_time.now = vec!["%H:%M".to_owned()];
+
+    assert_eq!(
+      provider.candidates("today", &config),
+      vec![
+        Candidate::new("2026-10-17", None),
+        Candidate::new("令和8年", None)
+      ]
+    );
+    assert_eq!(
+      provider.candidates("now", &config),
+      vec![Candidate::new("13:05", None)]
+    );
+    assert_eq!(provider.candidates("かんじ", &config), vec![]);
+  }
+}
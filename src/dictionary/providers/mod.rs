@@ -0,0 +1,77 @@
+mod date_time;
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::Candidate;
+use crate::RSKKConfig;
+
+pub use date_time::{DateTimeConfig, DateTimeProvider};
+
+// 辞書に登録された静的な候補に加えて、変換時に候補を生成するもの
+// 生成された候補は辞書の学習対象にならない
+pub trait DynamicCandidateProvider: Send + Sync {
+  fn candidates(&self, read: &str, config: &RSKKConfig) -> Vec<Candidate>;
+}
+
+pub trait Clock: Send + Sync {
+  // UNIX秒
+  fn now(&self) -> i64;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+  fn now(&self) -> i64 {
+    SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|duration| duration.as_secs() as i64)
+      .unwrap_or(0)
+  }
+}
+
+#[cfg(test)]
+pub struct FixedClock(pub i64);
+
+#[cfg(test)]
+impl Clock for FixedClock {
+  fn now(&self) -> i64 {
+    self.0
+  }
+}
+
+#[derive(Clone)]
+pub struct DynamicCandidateProviders {
+  providers: Vec<Arc<dyn DynamicCandidateProvider>>,
+}
+
+impl DynamicCandidateProviders {
+  pub fn new(providers: Vec<Arc<dyn DynamicCandidateProvider>>) -> Self {
+    DynamicCandidateProviders { providers }
+  }
+
+  pub fn push(&mut self, provider: Arc<dyn DynamicCandidateProvider>) {
+    self.providers.push(provider);
+  }
+
+  pub fn candidates(&self, read: &str, config: &RSKKConfig) -> Vec<Candidate> {
+    self
+      .providers
+      .iter()
+      .flat_map(|provider| provider.candidates(read, config))
+      .collect()
+  }
+}
+
+impl Default for DynamicCandidateProviders {
+  fn default() -> Self {
+    DynamicCandidateProviders::new(vec![Arc::new(DateTimeProvider::new(box SystemClock))])
+  }
+}
+
+impl fmt::Debug for DynamicCandidateProviders {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "DynamicCandidateProviders({})", self.providers.len())
+  }
+}
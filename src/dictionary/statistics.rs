@@ -0,0 +1,240 @@
+use super::{Candidate, DictionaryEntry};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash, Serialize, Deserialize)]
+pub enum RankingPolicy {
+  Dictionary,
+  MostRecentlyUsed,
+  Frequency,
+  Hybrid,
+}
+
+impl Default for RankingPolicy {
+  fn default() -> Self {
+    RankingPolicy::Dictionary
+  }
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct CandidateStatistic {
+  pub count: u64,
+  pub last_used: u64,
+}
+
+impl CandidateStatistic {
+  pub fn new(count: u64, last_used: u64) -> Self {
+    CandidateStatistic { count, last_used }
+  }
+}
+
+// SKK-JISYOの互換性を保つため、使用回数と最終使用時刻は辞書とは別のファイルに保存する
+// 形式は1行1候補で「読み\t候補\t使用回数\t最終使用時刻(UNIX秒)」
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct CandidateStatistics {
+  entries: HashMap<(String, String), CandidateStatistic>,
+}
+
+impl CandidateStatistics {
+  pub fn new() -> Self {
+    CandidateStatistics {
+      entries: HashMap::new(),
+    }
+  }
+
+  pub fn get(&self, read: &str, entry: &str) -> Option<&CandidateStatistic> {
+    self.entries.get(&(read.to_owned(), entry.to_owned()))
+  }
+
+  pub fn insert<S: Into<String>>(&mut self, read: S, entry: S, statistic: CandidateStatistic) {
+    self.entries.insert((read.into(), entry.into()), statistic);
+  }
+
+  pub fn record<S: Into<String>>(&mut self, read: S, entry: S, timestamp: u64) {
+    let statistic = self
+      .entries
+      .entry((read.into(), entry.into()))
+      .or_insert(CandidateStatistic::new(0, 0));
+
+    statistic.count += 1;
+    statistic.last_used = std::cmp::max(statistic.last_used, timestamp);
+  }
+
+  pub fn record_usage(&mut self, entry: &DictionaryEntry, timestamp: u64) {
+    entry
+      .candidates
+      .iter()
+      .for_each(|candidate| self.record(&entry.read as &str, &candidate.entry, timestamp));
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  pub fn rank(
+    &self,
+    read: &str,
+    candidates: &Vec<Candidate>,
+    policy: RankingPolicy,
+  ) -> Vec<Candidate> {
+    let mut ret = candidates.clone();
+    let statistic = |candidate: &Candidate| {
+      self
+        .get(read, &candidate.entry)
+        .map(|item| *item)
+        .unwrap_or(CandidateStatistic::new(0, 0))
+    };
+
+    match policy {
+      RankingPolicy::Dictionary => {}
+      RankingPolicy::MostRecentlyUsed => ret.sort_by_key(|c| Reverse(statistic(c).last_used)),
+      RankingPolicy::Frequency => {
+        ret.sort_by_key(|c| Reverse((statistic(c).count, statistic(c).last_used)))
+      }
+      RankingPolicy::Hybrid => {
+        // 直近に使った候補を先頭に置き、残りは使用回数順に並べる
+        ret.sort_by_key(|c| Reverse((statistic(c).count, statistic(c).last_used)));
+        let latest = ret
+          .iter()
+          .enumerate()
+          .filter(|(_, c)| statistic(c).last_used != 0)
+          .max_by_key(|(i, c)| (statistic(c).last_used, Reverse(*i)))
+          .map(|(i, _)| i);
+        if let Some(i) = latest {
+          let item = ret.remove(i);
+          ret.insert(0, item);
+        }
+      }
+    }
+
+    ret
+  }
+
+  pub fn parse(string: &str) -> Self {
+    let mut ret = CandidateStatistics::new();
+    string.lines().for_each(|line| {
+      if line.trim().is_empty() || line.starts_with(";;") {
+        return;
+      }
+
+      let items: Vec<_> = line.trim_end().split('\t').collect();
+      if let [read, entry, count, last_used] = &*items {
+        if let (Ok(count), Ok(last_used)) = (count.parse(), last_used.parse()) {
+          ret.insert(*read, *entry, CandidateStatistic::new(count, last_used));
+        }
+      }
+    });
+
+    ret
+  }
+
+  pub fn to_string(&self) -> String {
+    let mut items = self.entries.iter().collect::<Vec<_>>();
+    items.sort_by(|a, b| a.0.cmp(b.0));
+
+    items
+      .iter()
+      .fold(String::new(), |acc, ((read, entry), statistic)| {
+        acc
+          + &format!(
+            "{}\t{}\t{}\t{}\n",
+            read, entry, statistic.count, statistic.last_used
+          )
+      })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn candidates() -> Vec<Candidate> {
+    vec![
+      Candidate::new("a", None),
+      Candidate::new("b", None),
+      Candidate::new("c", None),
+    ]
+  }
+
+  fn entries(candidates: &Vec<Candidate>) -> Vec<&str> {
+    candidates.iter().map(|c| &c.entry as &str).collect()
+  }
+
+  #[test]
+  fn parse() {
+    let statistics =
+      CandidateStatistics::parse(";; comment\nかんじ\t漢字\t3\t100\n\nbroken\tline\n");
+    assert_eq!(
+      statistics.get("かんじ", "漢字"),
+      Some(&CandidateStatistic::new(3, 100))
+    );
+    assert_eq!(statistics.get("broken", "line"), None);
+
+    let statistics = CandidateStatistics::parse("かんじ\t漢字\tx\t100\n");
+    assert!(statistics.is_empty());
+  }
+
+  #[test]
+  fn to_string() {
+    let mut statistics = CandidateStatistics::new();
+    statistics.record("b", "y", 20);
+    statistics.record("a", "x", 10);
+    statistics.record("a", "x", 30);
+
+    assert_eq!(&statistics.to_string(), "a\tx\t2\t30\nb\ty\t1\t20\n");
+    assert_eq!(
+      CandidateStatistics::parse(&statistics.to_string()),
+      statistics
+    );
+  }
+
+  #[test]
+  fn record_usage() {
+    let mut statistics = CandidateStatistics::new();
+    statistics.record_usage(
+      &DictionaryEntry::new("かんじ", vec![Candidate::new("感じ", None)]),
+      100,
+    );
+
+    assert_eq!(
+      statistics.get("かんじ", "感じ"),
+      Some(&CandidateStatistic::new(1, 100))
+    );
+    assert_eq!(
+      entries(&statistics.rank(
+        "かんじ",
+        &vec![Candidate::new("漢字", None), Candidate::new("感じ", None)],
+        RankingPolicy::MostRecentlyUsed
+      )),
+      vec!["感じ", "漢字"]
+    );
+  }
+
+  #[test]
+  fn rank() {
+    let mut statistics = CandidateStatistics::new();
+    statistics.insert("r", "b", CandidateStatistic::new(1, 300));
+    statistics.insert("r", "c", CandidateStatistic::new(5, 100));
+
+    let rank = |policy| statistics.rank("r", &candidates(), policy);
+    assert_eq!(
+      entries(&rank(RankingPolicy::Dictionary)),
+      vec!["a", "b", "c"]
+    );
+    assert_eq!(
+      entries(&rank(RankingPolicy::MostRecentlyUsed)),
+      vec!["b", "c", "a"]
+    );
+    assert_eq!(
+      entries(&rank(RankingPolicy::Frequency)),
+      vec!["c", "b", "a"]
+    );
+    assert_eq!(entries(&rank(RankingPolicy::Hybrid)), vec!["b", "c", "a"]);
+
+    let statistics = CandidateStatistics::new();
+    assert_eq!(
+      entries(&statistics.rank("r", &candidates(), RankingPolicy::Hybrid)),
+      vec!["a", "b", "c"]
+    );
+  }
+}
@@ -0,0 +1,61 @@
+use super::us::US;
+use super::KeyboardLayout;
+
+// Colemak配列
+// キーコードはQWERTY配列の位置の文字で届くため、Colemak配列の文字に置き換えてから扱う
+const LAYOUT: &[(char, char)] = &[
+  ('e', 'f'),
+  ('r', 'p'),
+  ('t', 'g'),
+  ('y', 'j'),
+  ('u', 'l'),
+  ('i', 'u'),
+  ('o', 'y'),
+  ('p', ';'),
+  ('s', 'r'),
+  ('d', 's'),
+  ('f', 't'),
+  ('g', 'd'),
+  ('j', 'n'),
+  ('k', 'e'),
+  ('l', 'i'),
+  (';', 'o'),
+  ('n', 'k'),
+];
+
+pub fn layout() -> KeyboardLayout {
+  US::layout().remap(LAYOUT)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::keyboards::{KeyCode, Keyboard, Keyboards};
+
+  #[test]
+  fn last_character() {
+    let character = |events: &[&str]| {
+      let mut keyboard = Keyboards::Colemak.to_keyboard();
+      events
+        .iter()
+        .for_each(|key| keyboard.key_down(&KeyCode::from(*key)));
+      keyboard.last_character()?.printable_key()
+    };
+
+    assert_eq!(character(&["e"]), Some('f'));
+    assert_eq!(character(&["k"]), Some('e'));
+    assert_eq!(character(&["shift", "k"]), Some('E'));
+    assert_eq!(character(&["p"]), Some(';'));
+    assert_eq!(character(&["shift", "p"]), Some(':'));
+    assert_eq!(character(&["a"]), Some('a'));
+  }
+
+  #[test]
+  fn pressing_keys() {
+    let mut keyboard = Keyboards::Colemak.to_keyboard();
+    keyboard.key_down(&KeyCode::from("ctrl"));
+    keyboard.key_down(&KeyCode::from("y"));
+    assert!(keyboard.is_pressing(&KeyCode::from("j")));
+    keyboard.key_up(&KeyCode::from("y"));
+    assert!(!keyboard.is_pressing(&KeyCode::from("j")));
+  }
+}
@@ -0,0 +1,78 @@
+use super::us::US;
+use super::KeyboardLayout;
+
+// Dvorak配列
+// キーコードはQWERTY配列の位置の文字で届くため、Dvorak配列の文字に置き換えてから扱う
+const LAYOUT: &[(char, char)] = &[
+  ('-', '['),
+  ('=', ']'),
+  ('q', '\''),
+  ('w', ','),
+  ('e', '.'),
+  ('r', 'p'),
+  ('t', 'y'),
+  ('y', 'f'),
+  ('u', 'g'),
+  ('i', 'c'),
+  ('o', 'r'),
+  ('p', 'l'),
+  ('[', '/'),
+  (']', '='),
+  ('s', 'o'),
+  ('d', 'e'),
+  ('f', 'u'),
+  ('g', 'i'),
+  ('h', 'd'),
+  ('j', 'h'),
+  ('k', 't'),
+  ('l', 'n'),
+  (';', 's'),
+  ('\'', '-'),
+  ('z', ';'),
+  ('x', 'q'),
+  ('c', 'j'),
+  ('v', 'k'),
+  ('b', 'x'),
+  ('n', 'b'),
+  (',', 'w'),
+  ('.', 'v'),
+  ('/', 'z'),
+];
+
+pub fn layout() -> KeyboardLayout {
+  US::layout().remap(LAYOUT)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::keyboards::{KeyCode, Keyboard, Keyboards};
+
+  #[test]
+  fn last_character() {
+    let character = |events: &[&str]| {
+      let mut keyboard = Keyboards::Dvorak.to_keyboard();
+      events
+        .iter()
+        .for_each(|key| keyboard.key_down(&KeyCode::from(*key)));
+      keyboard.last_character()?.printable_key()
+    };
+
+    assert_eq!(character(&["q"]), Some('\''));
+    assert_eq!(character(&["d"]), Some('e'));
+    assert_eq!(character(&["shift", "d"]), Some('E'));
+    assert_eq!(character(&["-"]), Some('['));
+    assert_eq!(character(&["shift", "z"]), Some(':'));
+    assert_eq!(character(&["a"]), Some('a'));
+    assert_eq!(character(&["1"]), Some('1'));
+  }
+
+  #[test]
+  fn pressing_keys() {
+    let mut keyboard = Keyboards::Dvorak.to_keyboard();
+    keyboard.key_down(&KeyCode::from("ctrl"));
+    keyboard.key_down(&KeyCode::from("c"));
+    assert!(keyboard.is_pressing(&KeyCode::from("j")));
+    keyboard.key_up(&KeyCode::from("c"));
+    assert!(!keyboard.is_pressing(&KeyCode::from("j")));
+  }
+}
@@ -0,0 +1,131 @@
+// Linuxのevdevのキーコード(linux/input-event-codes.h)
+use super::{KeyCode, MetaKey};
+
+pub fn to_key_code(code: u16) -> Result<KeyCode, &'static str> {
+  match code {
+    1 => Ok(KeyCode::from("escape")),
+    2 => Ok(KeyCode::from("1")),
+    3 => Ok(KeyCode::from("2")),
+    4 => Ok(KeyCode::from("3")),
+    5 => Ok(KeyCode::from("4")),
+    6 => Ok(KeyCode::from("5")),
+    7 => Ok(KeyCode::from("6")),
+    8 => Ok(KeyCode::from("7")),
+    9 => Ok(KeyCode::from("8")),
+    10 => Ok(KeyCode::from("9")),
+    11 => Ok(KeyCode::from("0")),
+    12 => Ok(KeyCode::from("-")),
+    13 => Ok(KeyCode::from("=")),
+    14 => Ok(KeyCode::from("backspace")),
+    15 => Ok(KeyCode::from("tab")),
+    16 => Ok(KeyCode::from("q")),
+    17 => Ok(KeyCode::from("w")),
+    18 => Ok(KeyCode::from("e")),
+    19 => Ok(KeyCode::from("r")),
+    20 => Ok(KeyCode::from("t")),
+    21 => Ok(KeyCode::from("y")),
+    22 => Ok(KeyCode::from("u")),
+    23 => Ok(KeyCode::from("i")),
+    24 => Ok(KeyCode::from("o")),
+    25 => Ok(KeyCode::from("p")),
+    26 => Ok(KeyCode::from("[")),
+    27 => Ok(KeyCode::from("]")),
+    28 => Ok(KeyCode::from("enter")),
+    29 => Ok(KeyCode::from("left_control")),
+    30 => Ok(KeyCode::from("a")),
+    31 => Ok(KeyCode::from("s")),
+    32 => Ok(KeyCode::from("d")),
+    33 => Ok(KeyCode::from("f")),
+    34 => Ok(KeyCode::from("g")),
+    35 => Ok(KeyCode::from("h")),
+    36 => Ok(KeyCode::from("j")),
+    37 => Ok(KeyCode::from("k")),
+    38 => Ok(KeyCode::from("l")),
+    39 => Ok(KeyCode::from(";")),
+    40 => Ok(KeyCode::from("'")),
+    41 => Ok(KeyCode::from("`")),
+    42 => Ok(KeyCode::from("left_shift")),
+    43 => Ok(KeyCode::from("\\")),
+    44 => Ok(KeyCode::from("z")),
+    45 => Ok(KeyCode::from("x")),
+    46 => Ok(KeyCode::from("c")),
+    47 => Ok(KeyCode::from("v")),
+    48 => Ok(KeyCode::from("b")),
+    49 => Ok(KeyCode::from("n")),
+    50 => Ok(KeyCode::from("m")),
+    51 => Ok(KeyCode::from(",")),
+    52 => Ok(KeyCode::from(".")),
+    53 => Ok(KeyCode::from("/")),
+    54 => Ok(KeyCode::from("right_shift")),
+    55 => Ok(KeyCode::from("numpad_*")),
+    56 => Ok(KeyCode::from("left_option")),
+    57 => Ok(KeyCode::from("space")),
+    58 => Ok(KeyCode::from("caps_lock")),
+    59..=68 => Ok(KeyCode::Meta(MetaKey::F((code - 58) as u8))),
+    71 => Ok(KeyCode::from("numpad_7")),
+    72 => Ok(KeyCode::from("numpad_8")),
+    73 => Ok(KeyCode::from("numpad_9")),
+    74 => Ok(KeyCode::from("numpad_-")),
+    75 => Ok(KeyCode::from("numpad_4")),
+    76 => Ok(KeyCode::from("numpad_5")),
+    77 => Ok(KeyCode::from("numpad_6")),
+    78 => Ok(KeyCode::from("numpad_+")),
+    79 => Ok(KeyCode::from("numpad_1")),
+    80 => Ok(KeyCode::from("numpad_2")),
+    81 => Ok(KeyCode::from("numpad_3")),
+    82 => Ok(KeyCode::from("numpad_0")),
+    83 => Ok(KeyCode::from("numpad_.")),
+    87 => Ok(KeyCode::from("F11")),
+    88 => Ok(KeyCode::from("F12")),
+    89 => Ok(KeyCode::from("_")),
+    92 => Ok(KeyCode::from("henkan")),
+    93 => Ok(KeyCode::from("kana")),
+    94 => Ok(KeyCode::from("muhenkan")),
+    96 => Ok(KeyCode::from("enter")),
+    97 => Ok(KeyCode::from("right_control")),
+    98 => Ok(KeyCode::from("numpad_/")),
+    100 => Ok(KeyCode::from("right_option")),
+    102 => Ok(KeyCode::from("home")),
+    103 => Ok(KeyCode::from("arrow_up")),
+    104 => Ok(KeyCode::from("page_up")),
+    105 => Ok(KeyCode::from("arrow_left")),
+    106 => Ok(KeyCode::from("arrow_right")),
+    107 => Ok(KeyCode::from("end")),
+    108 => Ok(KeyCode::from("arrow_down")),
+    109 => Ok(KeyCode::from("page_down")),
+    111 => Ok(KeyCode::from("delete")),
+    117 => Ok(KeyCode::from("numpad_=")),
+    // Apple製JISキーボードのかなと英数
+    122 => Ok(KeyCode::from("kana")),
+    123 => Ok(KeyCode::from("eisu")),
+    124 => Ok(KeyCode::from("¥")),
+    125 => Ok(KeyCode::from("left_command")),
+    126 => Ok(KeyCode::from("right_command")),
+    _ => Err("unknown evdev code"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn to_key_code() {
+    assert_eq!(super::to_key_code(30), Ok(KeyCode::Printable('a')));
+    assert_eq!(super::to_key_code(16), Ok(KeyCode::Printable('q')));
+    assert_eq!(super::to_key_code(29), Ok(KeyCode::Meta(MetaKey::Ctrl)));
+    assert_eq!(
+      super::to_key_code(14),
+      Ok(KeyCode::Meta(MetaKey::Backspace))
+    );
+    assert_eq!(super::to_key_code(93), Ok(KeyCode::Meta(MetaKey::Kana)));
+    assert_eq!(super::to_key_code(102), Ok(KeyCode::Meta(MetaKey::Home)));
+    assert_eq!(super::to_key_code(59), Ok(KeyCode::Meta(MetaKey::F(1))));
+    assert_eq!(super::to_key_code(88), Ok(KeyCode::Meta(MetaKey::F(12))));
+    assert_eq!(
+      super::to_key_code(79),
+      Ok(KeyCode::PrintableMeta(MetaKey::Numpad, '1'))
+    );
+    assert!(super::to_key_code(0).is_err());
+  }
+}
@@ -0,0 +1,87 @@
+use super::layout::{KeyboardLayout, LayoutKey};
+
+// JIS配列(日本語106/109キーボード)
+// キーコードはUS配列の同じ位置のキーの文字で届くため、JIS配列の刻印の文字に置き換える
+
+// 英字以外のキーと、そのキーの文字、シフトを押したときの文字
+const SHIFT: &[(char, char, char)] = &[
+  ('1', '1', '!'),
+  ('2', '2', '"'),
+  ('3', '3', '#'),
+  ('4', '4', '$'),
+  ('5', '5', '%'),
+  ('6', '6', '&'),
+  ('7', '7', '\''),
+  ('8', '8', '('),
+  ('9', '9', ')'),
+  ('-', '-', '='),
+  ('=', '^', '~'),
+  ('¥', '¥', '|'),
+  ('[', '@', '`'),
+  (']', '[', '{'),
+  ('\\', ']', '}'),
+  (';', ';', '+'),
+  ('\'', ':', '*'),
+  (',', ',', '<'),
+  ('.', '.', '>'),
+  ('/', '/', '?'),
+];
+
+pub fn layout() -> KeyboardLayout {
+  let letters = ('a'..='z').map(|c| (c, c, c.to_ascii_uppercase()));
+  KeyboardLayout {
+    keys: letters
+      .chain(SHIFT.iter().cloned())
+      .map(|(key, normal, shift)| LayoutKey {
+        key: key.to_string(),
+        normal,
+        shift: Some(shift),
+      })
+      .collect(),
+    modifiers: vec![],
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::keyboards::KeyEvents::*;
+  use crate::keyboards::{KeyCode, Keyboard, Keyboards};
+
+  #[test]
+  fn last_character() {
+    let character = |events: &[&str]| {
+      let mut keyboard = Keyboards::JIS.to_keyboard();
+      events
+        .iter()
+        .for_each(|key| keyboard.key_down(&KeyCode::from(*key)));
+      keyboard.last_character()?.printable_key()
+    };
+
+    assert_eq!(character(&["a"]), Some('a'));
+    assert_eq!(character(&["shift", "a"]), Some('A'));
+    assert_eq!(character(&["shift", "2"]), Some('"'));
+    assert_eq!(character(&["shift", "7"]), Some('\''));
+    assert_eq!(character(&["="]), Some('^'));
+    assert_eq!(character(&["shift", "="]), Some('~'));
+    assert_eq!(character(&["["]), Some('@'));
+    assert_eq!(character(&["shift", "["]), Some('`'));
+    assert_eq!(character(&["'"]), Some(':'));
+    assert_eq!(character(&["shift", "'"]), Some('*'));
+    assert_eq!(character(&["shift", ";"]), Some('+'));
+    assert_eq!(character(&["¥"]), Some('¥'));
+    assert_eq!(character(&["shift", "¥"]), Some('|'));
+    assert_eq!(character(&["_"]), Some('_'));
+    assert_eq!(character(&["shift", "_"]), Some('_'));
+  }
+
+  #[test]
+  fn push_event() {
+    let mut keyboard = Keyboards::JIS.to_keyboard();
+    keyboard.push_events(&vec![
+      KeyDown(KeyCode::from("kana")),
+      KeyUp(KeyCode::from("kana")),
+    ]);
+    assert_eq!(keyboard.last_character(), Some(KeyCode::from("kana")));
+    assert!(keyboard.pressing_keys().is_empty());
+  }
+}
@@ -27,6 +27,22 @@ pub enum MetaKey {
   ArrowDown,
   ArrowLeft,
   ArrowUp,
+  // JISキーボードの日本語入力用のキー
+  Eisu,
+  Kana,
+  Henkan,
+  Muhenkan,
+  Home,
+  End,
+  PageUp,
+  PageDown,
+  Fn,
+  CapsLock,
+  Clear,
+  // ファンクションキー(F1〜F24)
+  F(u8),
+  // テンキー。PrintableMetaとして入力する文字と組で使う
+  Numpad,
 }
 
 impl KeyCode {
@@ -78,6 +94,26 @@ impl From<&str> for KeyCode {
       "arrow_down" => crate::keyboards::KeyCode::Meta(crate::keyboards::MetaKey::ArrowDown),
       "arrow_left" => crate::keyboards::KeyCode::Meta(crate::keyboards::MetaKey::ArrowLeft),
       "arrow_up" => crate::keyboards::KeyCode::Meta(crate::keyboards::MetaKey::ArrowUp),
+      "eisu" | "英数" => crate::keyboards::KeyCode::Meta(crate::keyboards::MetaKey::Eisu),
+      "kana" | "かな" => crate::keyboards::KeyCode::Meta(crate::keyboards::MetaKey::Kana),
+      "henkan" | "変換" => crate::keyboards::KeyCode::Meta(crate::keyboards::MetaKey::Henkan),
+      "muhenkan" | "無変換" => {
+        crate::keyboards::KeyCode::Meta(crate::keyboards::MetaKey::Muhenkan)
+      }
+      "home" => crate::keyboards::KeyCode::Meta(crate::keyboards::MetaKey::Home),
+      "end" => crate::keyboards::KeyCode::Meta(crate::keyboards::MetaKey::End),
+      "page_up" => crate::keyboards::KeyCode::Meta(crate::keyboards::MetaKey::PageUp),
+      "page_down" => crate::keyboards::KeyCode::Meta(crate::keyboards::MetaKey::PageDown),
+      "fn" => crate::keyboards::KeyCode::Meta(crate::keyboards::MetaKey::Fn),
+      "caps_lock" => crate::keyboards::KeyCode::Meta(crate::keyboards::MetaKey::CapsLock),
+      "clear" => crate::keyboards::KeyCode::Meta(crate::keyboards::MetaKey::Clear),
+      string if function_key(string).is_some() => {
+        crate::keyboards::KeyCode::Meta(crate::keyboards::MetaKey::F(function_key(string).unwrap()))
+      }
+      string if numpad_key(string).is_some() => crate::keyboards::KeyCode::PrintableMeta(
+        crate::keyboards::MetaKey::Numpad,
+        numpad_key(string).unwrap(),
+      ),
       "null" => crate::keyboards::KeyCode::Null,
       string if string == "" => crate::keyboards::KeyCode::Null,
       string => crate::keyboards::KeyCode::Printable(string.chars().next().unwrap()),
@@ -85,6 +121,28 @@ impl From<&str> for KeyCode {
   }
 }
 
+// 「F1」や「f12」
+fn function_key(name: &str) -> Option<u8> {
+  let number = name
+    .strip_prefix('F')
+    .or_else(|| name.strip_prefix('f'))?
+    .parse::<u8>()
+    .ok()?;
+  match number {
+    1..=24 => Some(number),
+    _ => None,
+  }
+}
+
+// 「numpad_1」や「numpad_+」
+fn numpad_key(name: &str) -> Option<char> {
+  let mut chars = name.strip_prefix("numpad_")?.chars();
+  match (chars.next(), chars.next()) {
+    (Some(character), None) => Some(character),
+    _ => None,
+  }
+}
+
 impl From<String> for KeyCode {
   fn from(name: String) -> KeyCode {
     Self::from(&name as &str)
@@ -181,27 +239,32 @@ impl TryFrom<u16> for KeyCode {
       126 => Ok(KeyCode::from("arrow_up")),
       125 => Ok(KeyCode::from("arrow_down")),
       63 => Ok(KeyCode::from("fn")),
-      116 => Ok(KeyCode::from("home")),
-      121 => Ok(KeyCode::from("end")),
-      115 => Ok(KeyCode::from("page_up")),
-      119 => Ok(KeyCode::from("page_down")),
+      115 => Ok(KeyCode::from("home")),
+      119 => Ok(KeyCode::from("end")),
+      116 => Ok(KeyCode::from("page_up")),
+      121 => Ok(KeyCode::from("page_down")),
       71 => Ok(KeyCode::from("clear")),
-      81 => Ok(KeyCode::from("=")),
-      75 => Ok(KeyCode::from("/")),
-      67 => Ok(KeyCode::from("*")),
-      78 => Ok(KeyCode::from("–")),
-      69 => Ok(KeyCode::from("+")),
+      81 => Ok(KeyCode::from("numpad_=")),
+      75 => Ok(KeyCode::from("numpad_/")),
+      67 => Ok(KeyCode::from("numpad_*")),
+      78 => Ok(KeyCode::from("numpad_-")),
+      65 => Ok(KeyCode::from("numpad_.")),
+      69 => Ok(KeyCode::from("numpad_+")),
       76 => Ok(KeyCode::from("enter")),
-      82 => Ok(KeyCode::from("0")),
-      83 => Ok(KeyCode::from("1")),
-      84 => Ok(KeyCode::from("2")),
-      85 => Ok(KeyCode::from("3")),
-      86 => Ok(KeyCode::from("4")),
-      87 => Ok(KeyCode::from("5")),
-      88 => Ok(KeyCode::from("6")),
-      89 => Ok(KeyCode::from("7")),
-      91 => Ok(KeyCode::from("8")),
-      92 => Ok(KeyCode::from("9")),
+      82 => Ok(KeyCode::from("numpad_0")),
+      83 => Ok(KeyCode::from("numpad_1")),
+      84 => Ok(KeyCode::from("numpad_2")),
+      85 => Ok(KeyCode::from("numpad_3")),
+      86 => Ok(KeyCode::from("numpad_4")),
+      87 => Ok(KeyCode::from("numpad_5")),
+      88 => Ok(KeyCode::from("numpad_6")),
+      89 => Ok(KeyCode::from("numpad_7")),
+      91 => Ok(KeyCode::from("numpad_8")),
+      92 => Ok(KeyCode::from("numpad_9")),
+      93 => Ok(KeyCode::from("¥")),
+      94 => Ok(KeyCode::from("_")),
+      102 => Ok(KeyCode::from("eisu")),
+      104 => Ok(KeyCode::from("kana")),
       _ => Err(""),
     }
   }
@@ -218,6 +281,11 @@ impl KeyCombination {
   pub fn fulfilled(&self, pressed: &HashSet<KeyCode>) -> bool {
     self.0.iter().all(|&k| pressed.iter().any(|&kk| k == kk))
   }
+
+  // 押されているキーが組み合わせと過不足なく一致する
+  pub fn matches(&self, pressed: &HashSet<KeyCode>) -> bool {
+    &self.0 == pressed
+  }
 }
 
 impl Hash for KeyCombination {
@@ -244,6 +312,23 @@ impl KeyCombinations {
       .iter()
       .any(|combination| combination.fulfilled(pressed))
   }
+
+  pub fn matches(&self, pressed: &HashSet<KeyCode>) -> bool {
+    self
+      .0
+      .iter()
+      .any(|combination| combination.matches(pressed))
+  }
+
+  // 押されているキーを満たす組み合わせのうち、最もキー数が多いもののキー数
+  pub fn fulfilled_len(&self, pressed: &HashSet<KeyCode>) -> Option<usize> {
+    self
+      .0
+      .iter()
+      .filter(|combination| combination.fulfilled(pressed))
+      .map(|combination| combination.0.len())
+      .max()
+  }
 }
 
 #[cfg(test)]
@@ -252,6 +337,35 @@ mod tests {
   use crate::set;
   use crate::{combo, combos};
 
+  #[test]
+  fn try_from() {
+    assert_eq!(KeyCode::try_from(0), Ok(KeyCode::from("a")));
+    assert_eq!(KeyCode::try_from(93), Ok(KeyCode::Printable('¥')));
+    assert_eq!(KeyCode::try_from(94), Ok(KeyCode::Printable('_')));
+    assert_eq!(KeyCode::try_from(102), Ok(KeyCode::Meta(MetaKey::Eisu)));
+    assert_eq!(KeyCode::try_from(104), Ok(KeyCode::Meta(MetaKey::Kana)));
+    assert_eq!(KeyCode::from("変換"), KeyCode::Meta(MetaKey::Henkan));
+    assert_eq!(KeyCode::from("無変換"), KeyCode::Meta(MetaKey::Muhenkan));
+    assert_eq!(KeyCode::try_from(115), Ok(KeyCode::Meta(MetaKey::Home)));
+    assert_eq!(KeyCode::try_from(119), Ok(KeyCode::Meta(MetaKey::End)));
+    assert_eq!(KeyCode::try_from(116), Ok(KeyCode::Meta(MetaKey::PageUp)));
+    assert_eq!(KeyCode::try_from(121), Ok(KeyCode::Meta(MetaKey::PageDown)));
+    assert_eq!(KeyCode::try_from(63), Ok(KeyCode::Meta(MetaKey::Fn)));
+    assert_eq!(KeyCode::try_from(57), Ok(KeyCode::Meta(MetaKey::CapsLock)));
+    assert_eq!(KeyCode::try_from(122), Ok(KeyCode::Meta(MetaKey::F(1))));
+    assert_eq!(KeyCode::try_from(80), Ok(KeyCode::Meta(MetaKey::F(19))));
+    assert_eq!(
+      KeyCode::try_from(78),
+      Ok(KeyCode::PrintableMeta(MetaKey::Numpad, '-'))
+    );
+    assert_eq!(
+      KeyCode::try_from(83),
+      Ok(KeyCode::PrintableMeta(MetaKey::Numpad, '1'))
+    );
+    assert_eq!(KeyCode::from("f"), KeyCode::Printable('f'));
+    assert_eq!(KeyCode::from("f25"), KeyCode::Printable('f'));
+  }
+
   mod key_combination {
     use super::*;
 
@@ -262,6 +376,19 @@ mod tests {
       assert!(combination.fulfilled(&set![KeyCode::from("ctrl"), KeyCode::from("j")]));
       assert!(!combination.fulfilled(&set![KeyCode::from("a")]));
     }
+
+    #[test]
+    fn matches() {
+      let combination = combo![KeyCode::from("ctrl"), KeyCode::from("j")];
+
+      assert!(combination.matches(&set![KeyCode::from("ctrl"), KeyCode::from("j")]));
+      assert!(!combination.matches(&set![
+        KeyCode::from("ctrl"),
+        KeyCode::from("alt"),
+        KeyCode::from("j")
+      ]));
+      assert!(!combination.matches(&set![KeyCode::from("j")]));
+    }
   }
 
   mod key_combinations {
@@ -279,5 +406,23 @@ mod tests {
       assert!(combination.fulfilled(&set![KeyCode::from("ctrl"), KeyCode::from("j")]));
       assert!(!combination.fulfilled(&set![KeyCode::from("b")]));
     }
+
+    #[test]
+    fn fulfilled_len() {
+      let combination = combos![
+        combo![KeyCode::from("q")],
+        combo![KeyCode::from("ctrl"), KeyCode::from("q")]
+      ];
+
+      assert_eq!(
+        combination.fulfilled_len(&set![KeyCode::from("q")]),
+        Some(1)
+      );
+      assert_eq!(
+        combination.fulfilled_len(&set![KeyCode::from("ctrl"), KeyCode::from("q")]),
+        Some(2)
+      );
+      assert_eq!(combination.fulfilled_len(&set![KeyCode::from("b")]), None);
+    }
   }
 }
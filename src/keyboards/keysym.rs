@@ -0,0 +1,172 @@
+// X11/XKBのkeysym(X11/keysymdef.h)
+// keysymは配列とシフトを反映した文字で届くため、英字は小文字に戻し、記号はそのまま使う
+use super::layout::{KeyboardLayout, LayoutKey, LayoutKeyboard};
+use super::{KeyCode, Keyboard, MetaKey};
+use std::collections::HashSet;
+
+// 英字のシフトだけを扱い、記号は配列によって読み替えないキーボード
+#[derive(Clone)]
+pub struct KeysymKeyboard {
+  keyboard: LayoutKeyboard,
+}
+
+impl Keyboard for KeysymKeyboard {
+  fn pressing_keys(&self) -> &HashSet<KeyCode> {
+    self.keyboard.pressing_keys()
+  }
+
+  fn last_character(&self) -> Option<KeyCode> {
+    self.keyboard.last_character()
+  }
+
+  fn key_down(&mut self, key: &KeyCode) {
+    self.keyboard.key_down(key);
+  }
+
+  // 1を押したあとシフトを離すと、押した「!」ではなく「1」を離したことになる
+  // 押していない文字のキーを離したときは、押したままの文字のキーをすべて離す
+  fn key_up(&mut self, key: &KeyCode) {
+    match key {
+      KeyCode::Printable(_) if !self.keyboard.is_pressing(key) => self
+        .keyboard
+        .pressing_keys()
+        .iter()
+        .filter(|key| matches!(key, KeyCode::Printable(_)))
+        .cloned()
+        .collect::<Vec<_>>()
+        .iter()
+        .for_each(|key| self.keyboard.key_up(key)),
+      _ => self.keyboard.key_up(key),
+    }
+  }
+}
+
+pub fn to_keyboard() -> Box<dyn Keyboard> {
+  let layout = KeyboardLayout {
+    keys: ('a'..='z')
+      .map(|c| LayoutKey {
+        key: c.to_string(),
+        normal: c,
+        shift: Some(c.to_ascii_uppercase()),
+      })
+      .collect(),
+    modifiers: vec![],
+  };
+
+  Box::new(KeysymKeyboard {
+    keyboard: LayoutKeyboard::new(&layout).unwrap(),
+  })
+}
+
+pub fn to_key_code(keysym: u32) -> Result<KeyCode, &'static str> {
+  match keysym {
+    0x20 => Ok(KeyCode::from("space")),
+    0x41..=0x5a => Ok(KeyCode::Printable(
+      (keysym as u8).to_ascii_lowercase() as char
+    )),
+    0x21..=0x7e => Ok(KeyCode::Printable(keysym as u8 as char)),
+    0xa5 => Ok(KeyCode::from("¥")),
+    0xff08 => Ok(KeyCode::from("backspace")),
+    0xff09 => Ok(KeyCode::from("tab")),
+    0xff0b => Ok(KeyCode::from("clear")),
+    0xff0d | 0xff8d => Ok(KeyCode::from("enter")),
+    0xff1b => Ok(KeyCode::from("escape")),
+    0xff22 => Ok(KeyCode::from("muhenkan")),
+    0xff23 => Ok(KeyCode::from("henkan")),
+    0xff27 => Ok(KeyCode::from("kana")),
+    0xff30 => Ok(KeyCode::from("eisu")),
+    0xff50 => Ok(KeyCode::from("home")),
+    0xff51 => Ok(KeyCode::from("arrow_left")),
+    0xff52 => Ok(KeyCode::from("arrow_up")),
+    0xff53 => Ok(KeyCode::from("arrow_right")),
+    0xff54 => Ok(KeyCode::from("arrow_down")),
+    0xff55 => Ok(KeyCode::from("page_up")),
+    0xff56 => Ok(KeyCode::from("page_down")),
+    0xff57 => Ok(KeyCode::from("end")),
+    0xffaa => Ok(KeyCode::from("numpad_*")),
+    0xffab => Ok(KeyCode::from("numpad_+")),
+    0xffad => Ok(KeyCode::from("numpad_-")),
+    0xffae => Ok(KeyCode::from("numpad_.")),
+    0xffaf => Ok(KeyCode::from("numpad_/")),
+    0xffb0..=0xffb9 => Ok(KeyCode::PrintableMeta(
+      MetaKey::Numpad,
+      (b'0' + (keysym - 0xffb0) as u8) as char,
+    )),
+    0xffbd => Ok(KeyCode::from("numpad_=")),
+    0xffbe..=0xffd5 => Ok(KeyCode::Meta(MetaKey::F((keysym - 0xffbd) as u8))),
+    0xffe1 => Ok(KeyCode::from("left_shift")),
+    0xffe2 => Ok(KeyCode::from("right_shift")),
+    0xffe3 => Ok(KeyCode::from("left_control")),
+    0xffe4 => Ok(KeyCode::from("right_control")),
+    0xffe5 => Ok(KeyCode::from("caps_lock")),
+    0xffe9 => Ok(KeyCode::from("left_option")),
+    0xffea => Ok(KeyCode::from("right_option")),
+    0xffeb => Ok(KeyCode::from("left_command")),
+    0xffec => Ok(KeyCode::from("right_command")),
+    0xffff => Ok(KeyCode::from("delete")),
+    _ => Err("unknown keysym"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn to_key_code() {
+    assert_eq!(super::to_key_code(0x61), Ok(KeyCode::Printable('a')));
+    assert_eq!(super::to_key_code(0x41), Ok(KeyCode::Printable('a')));
+    assert_eq!(super::to_key_code(0x21), Ok(KeyCode::Printable('!')));
+    assert_eq!(super::to_key_code(0x3a), Ok(KeyCode::Printable(':')));
+    assert_eq!(super::to_key_code(0x2c), Ok(KeyCode::Printable(',')));
+    assert_eq!(super::to_key_code(0x20), Ok(KeyCode::from("space")));
+    assert_eq!(super::to_key_code(0xffe3), Ok(KeyCode::Meta(MetaKey::Ctrl)));
+    assert_eq!(super::to_key_code(0xff27), Ok(KeyCode::Meta(MetaKey::Kana)));
+    assert_eq!(super::to_key_code(0xff50), Ok(KeyCode::Meta(MetaKey::Home)));
+    assert_eq!(super::to_key_code(0xffbe), Ok(KeyCode::Meta(MetaKey::F(1))));
+    assert_eq!(
+      super::to_key_code(0xffb1),
+      Ok(KeyCode::PrintableMeta(MetaKey::Numpad, '1'))
+    );
+    assert!(super::to_key_code(0x3042).is_err());
+  }
+
+  #[test]
+  fn to_keyboard() {
+    let character = |events: &[u32]| {
+      let mut keyboard = super::to_keyboard();
+      events
+        .iter()
+        .for_each(|keysym| keyboard.key_down(&super::to_key_code(*keysym).unwrap()));
+      keyboard.last_character()?.printable_key()
+    };
+
+    assert_eq!(character(&[0x61]), Some('a'));
+    assert_eq!(character(&[0xffe1, 0x41]), Some('A'));
+    assert_eq!(character(&[0xffe1, 0x27]), Some('\''));
+    assert_eq!(character(&[0x40]), Some('@'));
+    assert_eq!(character(&[0xffe1, 0x2b]), Some('+'));
+  }
+
+  #[test]
+  fn key_up_after_shift() {
+    let key = |keysym: u32| super::to_key_code(keysym).unwrap();
+    let mut keyboard = super::to_keyboard();
+
+    // シフトを先に離すと、「!」で押したキーが「1」で離される
+    keyboard.key_down(&key(0xffe1));
+    keyboard.key_down(&key(0x21));
+    keyboard.key_up(&key(0xffe1));
+    assert!(keyboard.is_pressing(&KeyCode::Printable('!')));
+    keyboard.key_up(&key(0x31));
+    assert!(keyboard.pressing_keys().is_empty());
+
+    keyboard.key_down(&key(0xffe3));
+    keyboard.key_down(&key(0x61));
+    keyboard.key_down(&key(0x62));
+    keyboard.key_up(&key(0x61));
+    assert!(keyboard.is_pressing(&KeyCode::Printable('b')));
+    assert!(keyboard.is_pressing(&KeyCode::Meta(MetaKey::Ctrl)));
+    assert!(!keyboard.is_pressing(&KeyCode::Printable('a')));
+  }
+}
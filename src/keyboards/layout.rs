@@ -0,0 +1,273 @@
+use super::{KeyCode, Keyboard, MetaKey};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+// 設定で定義するキーボード配列
+// keyはUS配列の位置の文字で、そのキーを押したときの文字とシフトを押したときの文字を書く
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct LayoutKey {
+  pub key: String,
+  pub normal: char,
+  #[serde(default)]
+  pub shift: Option<char>,
+}
+
+// keyをctrlなどの修飾キーとして扱う
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct LayoutModifier {
+  pub key: String,
+  pub modifier: String,
+}
+
+#[derive(Eq, PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct KeyboardLayout {
+  pub keys: Vec<LayoutKey>,
+  #[serde(default)]
+  pub modifiers: Vec<LayoutModifier>,
+}
+
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum LayoutError {
+  EmptyLayout,
+  NotPrintableKey(String),
+  NotModifier(String),
+  DuplicateKey(String),
+}
+
+impl fmt::Display for LayoutError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      LayoutError::EmptyLayout => write!(f, "layout has no keys"),
+      LayoutError::NotPrintableKey(key) => write!(f, "not a printable key: {}", key),
+      LayoutError::NotModifier(key) => write!(f, "not a modifier key: {}", key),
+      LayoutError::DuplicateKey(key) => write!(f, "duplicate key: {}", key),
+    }
+  }
+}
+
+impl KeyboardLayout {
+  pub fn validate(&self) -> Result<(), LayoutError> {
+    LayoutKeyboard::new(self).map(|_| ())
+  }
+
+  // keyの位置の文字を置き換えた配列を作る。シフトを押したときは、この配列で置き換えた文字を打つときの文字にする
+  pub fn remap(&self, remapped: &[(char, char)]) -> Self {
+    let shift = |character: char| {
+      self
+        .keys
+        .iter()
+        .find(|item| item.normal == character)
+        .and_then(|item| item.shift)
+    };
+
+    KeyboardLayout {
+      keys: self
+        .keys
+        .iter()
+        .map(|item| {
+          let normal = remapped
+            .iter()
+            .find(|(key, _)| item.key == key.to_string())
+            .map_or(item.normal, |(_, mapped)| *mapped);
+          LayoutKey {
+            key: item.key.clone(),
+            normal,
+            shift: shift(normal),
+          }
+        })
+        .collect(),
+      modifiers: self.modifiers.clone(),
+    }
+  }
+}
+
+#[derive(Clone)]
+pub struct LayoutKeyboard {
+  keys: HashMap<KeyCode, (char, Option<char>)>,
+  modifiers: HashMap<KeyCode, KeyCode>,
+  pressing_keys: HashSet<KeyCode>,
+  last_character: Option<KeyCode>,
+}
+
+impl LayoutKeyboard {
+  pub fn new(layout: &KeyboardLayout) -> Result<Self, LayoutError> {
+    if layout.keys.is_empty() {
+      return Err(LayoutError::EmptyLayout);
+    }
+
+    let mut keys = HashMap::new();
+    for item in &layout.keys {
+      let key = KeyCode::from(&item.key as &str);
+      if !key.is_printable() {
+        return Err(LayoutError::NotPrintableKey(item.key.clone()));
+      }
+      if keys.insert(key, (item.normal, item.shift)).is_some() {
+        return Err(LayoutError::DuplicateKey(item.key.clone()));
+      }
+    }
+
+    let mut modifiers = HashMap::new();
+    for item in &layout.modifiers {
+      let key = KeyCode::from(&item.key as &str);
+      let modifier = match KeyCode::from(&item.modifier as &str) {
+        modifier @ KeyCode::Meta(MetaKey::Ctrl)
+        | modifier @ KeyCode::Meta(MetaKey::Shift)
+        | modifier @ KeyCode::Meta(MetaKey::Alt)
+        | modifier @ KeyCode::Meta(MetaKey::Super) => modifier,
+        _ => return Err(LayoutError::NotModifier(item.modifier.clone())),
+      };
+      if key == KeyCode::Null {
+        return Err(LayoutError::NotModifier(item.key.clone()));
+      }
+      if keys.contains_key(&key) || modifiers.insert(key, modifier).is_some() {
+        return Err(LayoutError::DuplicateKey(item.key.clone()));
+      }
+    }
+
+    Ok(LayoutKeyboard {
+      keys,
+      modifiers,
+      pressing_keys: HashSet::new(),
+      last_character: None,
+    })
+  }
+
+  fn convert(&self, key: &KeyCode, is_shift_down: bool) -> Option<char> {
+    let character = key.printable_key()?;
+    Some(match self.keys.get(key) {
+      Some((normal, shift)) if is_shift_down => shift.unwrap_or(*normal),
+      Some((normal, _)) => *normal,
+      None => character,
+    })
+  }
+
+  // キー設定が配列の文字で書けるよう、押しているキーは配列の文字で持つ
+  fn pressing_key(&self, key: &KeyCode) -> KeyCode {
+    match self.keys.get(key) {
+      Some((normal, _)) => KeyCode::Printable(*normal),
+      None => *key,
+    }
+  }
+
+  fn physical_key(&self, key: &KeyCode) -> KeyCode {
+    self.modifiers.get(key).cloned().unwrap_or(*key)
+  }
+}
+
+impl Keyboard for LayoutKeyboard {
+  fn pressing_keys(&self) -> &HashSet<KeyCode> {
+    &self.pressing_keys
+  }
+
+  fn last_character(&self) -> Option<KeyCode> {
+    let key = self.last_character?;
+    match &key {
+      KeyCode::Printable(_) => Some(KeyCode::Printable(
+        self.convert(&key, self.is_pressing_shift())?,
+      )),
+      KeyCode::PrintableMeta(meta, _) => Some(KeyCode::PrintableMeta(
+        *meta,
+        self.convert(&key, self.is_pressing_shift())?,
+      )),
+      _ => Some(key),
+    }
+  }
+
+  fn key_down(&mut self, key: &KeyCode) {
+    let key = self.physical_key(key);
+    self.pressing_keys.insert(self.pressing_key(&key));
+    self.last_character = Some(key);
+  }
+
+  fn key_up(&mut self, key: &KeyCode) {
+    let key = self.pressing_key(&self.physical_key(key));
+    self.pressing_keys.remove(&key);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn layout(json: &str) -> Result<LayoutKeyboard, LayoutError> {
+    LayoutKeyboard::new(&serde_json::from_str(json).unwrap())
+  }
+
+  #[test]
+  fn new() {
+    assert!(layout(r#"{ "keys": [{ "key": "a", "normal": "a" }] }"#).is_ok());
+    assert_eq!(
+      layout(r#"{ "keys": [] }"#).err(),
+      Some(LayoutError::EmptyLayout)
+    );
+    assert_eq!(
+      layout(r#"{ "keys": [{ "key": "escape", "normal": "a" }] }"#).err(),
+      Some(LayoutError::NotPrintableKey("escape".to_owned()))
+    );
+    assert_eq!(
+      layout(r#"{ "keys": [{ "key": "a", "normal": "a" }, { "key": "a", "normal": "b" }] }"#).err(),
+      Some(LayoutError::DuplicateKey("a".to_owned()))
+    );
+    assert_eq!(
+      layout(
+        r#"{ "keys": [{ "key": "a", "normal": "a" }],
+             "modifiers": [{ "key": "kana", "modifier": "enter" }] }"#
+      )
+      .err(),
+      Some(LayoutError::NotModifier("enter".to_owned()))
+    );
+    assert_eq!(
+      layout(
+        r#"{ "keys": [{ "key": "a", "normal": "a" }],
+             "modifiers": [{ "key": "a", "modifier": "ctrl" }] }"#
+      )
+      .err(),
+      Some(LayoutError::DuplicateKey("a".to_owned()))
+    );
+    assert_eq!(
+      LayoutError::DuplicateKey("a".to_owned()).to_string(),
+      "duplicate key: a"
+    );
+  }
+
+  #[test]
+  fn last_character() {
+    let mut keyboard = layout(
+      r#"{ "keys": [{ "key": "q", "normal": "'", "shift": "\"" }, { "key": "x", "normal": "q" }],
+           "modifiers": [{ "key": "kana", "modifier": "ctrl" }] }"#,
+    )
+    .unwrap();
+
+    keyboard.key_down(&KeyCode::from("q"));
+    assert_eq!(keyboard.last_character(), Some(KeyCode::Printable('\'')));
+    assert!(keyboard.is_pressing(&KeyCode::Printable('\'')));
+    keyboard.key_up(&KeyCode::from("q"));
+    assert!(keyboard.pressing_keys().is_empty());
+
+    keyboard.key_down(&KeyCode::from("shift"));
+    keyboard.key_down(&KeyCode::from("q"));
+    assert_eq!(keyboard.last_character(), Some(KeyCode::Printable('"')));
+    keyboard.key_down(&KeyCode::from("x"));
+    assert_eq!(keyboard.last_character(), Some(KeyCode::Printable('q')));
+    keyboard.key_down(&KeyCode::from("b"));
+    assert_eq!(keyboard.last_character(), Some(KeyCode::Printable('b')));
+
+    keyboard.key_down(&KeyCode::from("kana"));
+    assert!(keyboard.is_pressing_ctrl());
+    keyboard.key_up(&KeyCode::from("kana"));
+    assert!(!keyboard.is_pressing_ctrl());
+  }
+
+  #[test]
+  fn remap() {
+    let remapped = super::super::us::US::layout().remap(&[('q', '\''), ('z', 'q')]);
+    let key = |key: &str| {
+      let item = remapped.keys.iter().find(|item| item.key == key)?;
+      Some((item.normal, item.shift?))
+    };
+
+    assert_eq!(key("q"), Some(('\'', '"')));
+    assert_eq!(key("z"), Some(('q', 'Q')));
+    assert_eq!(key("a"), Some(('a', 'A')));
+  }
+}
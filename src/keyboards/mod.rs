@@ -1,21 +1,103 @@
+pub mod colemak;
+pub mod dvorak;
+pub mod evdev;
+pub mod jis;
 pub mod keycodes;
+pub mod keysym;
+pub mod layout;
+pub mod simultaneous;
 pub mod us;
+pub mod windows;
 
 use objekt;
 use std::collections::HashSet;
 use std::convert::TryFrom;
 
 pub use keycodes::{KeyCode, KeyCombination, KeyCombinations, MetaKey};
+pub use layout::{KeyboardLayout, LayoutError, LayoutKeyboard};
+pub use simultaneous::{SimultaneousDetector, Stroke, Thumb, ThumbShiftConfig};
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug, Hash, Serialize, Deserialize)]
 pub enum Keyboards {
   US,
+  JIS,
+  Dvorak,
+  Colemak,
+  // 設定のkeyboard_layoutで定義した配列
+  Custom,
 }
 
 impl Keyboards {
   pub fn to_keyboard(&self) -> Box<dyn Keyboard> {
     match self {
       Keyboards::US => Box::new(us::US::new()),
+      Keyboards::JIS => Box::new(LayoutKeyboard::new(&jis::layout()).unwrap()),
+      Keyboards::Dvorak => Box::new(LayoutKeyboard::new(&dvorak::layout()).unwrap()),
+      Keyboards::Colemak => Box::new(LayoutKeyboard::new(&colemak::layout()).unwrap()),
+      Keyboards::Custom => Box::new(us::US::new()),
+    }
+  }
+}
+
+// フロントエンドから渡されるキーコードの種類
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyCodeSpace {
+  MacOS,
+  Evdev,
+  Keysym,
+  Windows,
+}
+
+impl Default for KeyCodeSpace {
+  fn default() -> Self {
+    KeyCodeSpace::MacOS
+  }
+}
+
+impl TryFrom<u8> for KeyCodeSpace {
+  type Error = &'static str;
+
+  fn try_from(space: u8) -> Result<Self, Self::Error> {
+    match space {
+      0 => Ok(KeyCodeSpace::MacOS),
+      1 => Ok(KeyCodeSpace::Evdev),
+      2 => Ok(KeyCodeSpace::Keysym),
+      3 => Ok(KeyCodeSpace::Windows),
+      _ => Err("unknown key code space"),
+    }
+  }
+}
+
+impl KeyCodeSpace {
+  // Windowsの仮想キーコードだけは、キーボードの配列によって読み替える
+  pub fn to_key_code(
+    &self,
+    code: u32,
+    keyboard_type: Keyboards,
+  ) -> Result<KeyCode, &'static str> {
+    match self {
+      KeyCodeSpace::MacOS => KeyCode::try_from(u16::try_from(code).or(Err(""))?),
+      KeyCodeSpace::Evdev => evdev::to_key_code(u16::try_from(code).or(Err(""))?),
+      KeyCodeSpace::Keysym => keysym::to_key_code(code),
+      KeyCodeSpace::Windows => {
+        windows::to_key_code(u16::try_from(code).or(Err(""))?, keyboard_type)
+      }
+    }
+  }
+
+  pub fn to_key_event(
+    &self,
+    event_type: u16,
+    code: u32,
+    keyboard_type: Keyboards,
+  ) -> Result<KeyEvents, &'static str> {
+    let code = self.to_key_code(code, keyboard_type)?;
+    match event_type {
+      1 => Ok(KeyEvents::KeyDown(code)),
+      2 => Ok(KeyEvents::KeyUp(code)),
+      3 => Ok(KeyEvents::KeyRepeat(code)),
+      _ => Err(""),
     }
   }
 }
@@ -25,6 +107,8 @@ pub enum KeyEvents {
   KeyDown(KeyCode),
   KeyRepeat(KeyCode),
   KeyUp(KeyCode),
+  // 入力元で確定済みの文字。キーボード配列を通さずに入力する
+  Character(char),
 }
 
 impl TryFrom<(u16, u16)> for KeyEvents {
@@ -32,17 +116,37 @@ impl TryFrom<(u16, u16)> for KeyEvents {
 
   fn try_from(pair: (u16, u16)) -> Result<Self, Self::Error> {
     let (event_type, code) = pair;
-    KeyCode::try_from(code)
-      .map(|code| match event_type {
-        1 => Ok(KeyEvents::KeyDown(code)),
-        2 => Ok(KeyEvents::KeyUp(code)),
-        _ => Err(""),
-      })
-      .unwrap_or(Err(""))
+    KeyCodeSpace::MacOS
+      .to_key_event(event_type, code as u32, Keyboards::US)
+      .or(Err(""))
+  }
+}
+
+// 同時打鍵の判定に使う、押した時刻(ミリ秒)付きのイベント
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub struct TimedKeyEvent {
+  pub event: KeyEvents,
+  pub timestamp: u64,
+}
+
+impl TimedKeyEvent {
+  pub fn new(event: KeyEvents, timestamp: u64) -> Self {
+    TimedKeyEvent { event, timestamp }
+  }
+}
+
+impl TryFrom<(u16, u16, u64)> for TimedKeyEvent {
+  type Error = &'static str;
+
+  fn try_from((event_type, code, timestamp): (u16, u16, u64)) -> Result<Self, Self::Error> {
+    Ok(TimedKeyEvent::new(
+      KeyEvents::try_from((event_type, code))?,
+      timestamp,
+    ))
   }
 }
 
-pub trait Keyboard: objekt::Clone {
+pub trait Keyboard: objekt::Clone + Send {
   fn key_down(&mut self, key: &KeyCode);
   fn key_up(&mut self, key: &KeyCode);
   fn pressing_keys(&self) -> &HashSet<KeyCode>;
@@ -59,7 +163,9 @@ pub trait Keyboard: objekt::Clone {
     match event {
       KeyEvents::KeyDown(key) => self.key_down(key),
       KeyEvents::KeyUp(key) => self.key_up(key),
-      KeyEvents::KeyRepeat(_) => unimplemented!(),
+      // キーリピートは押したままのキーをもう一度押したものとして扱う
+      KeyEvents::KeyRepeat(key) => self.key_down(key),
+      KeyEvents::Character(_) => {}
     }
   }
 
@@ -0,0 +1,222 @@
+// 親指シフトの同時打鍵の判定
+// 文字キーと親指キーが判定時間内に押されたら同時打鍵とし、そうでなければ単独打鍵とする
+use super::{KeyCode, KeyEvents, TimedKeyEvent};
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash, Serialize, Deserialize)]
+pub enum Thumb {
+  Left,
+  Right,
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Stroke {
+  Single(KeyCode),
+  Shifted(KeyCode, Thumb),
+}
+
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ThumbShiftConfig {
+  // 同時打鍵とみなすキーを押した間隔(ミリ秒)
+  pub window: u64,
+  pub left_thumb: KeyCode,
+  pub right_thumb: KeyCode,
+}
+
+impl Default for ThumbShiftConfig {
+  fn default() -> Self {
+    ThumbShiftConfig {
+      window: 100,
+      left_thumb: KeyCode::from("space"),
+      right_thumb: KeyCode::from("alt"),
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct SimultaneousDetector {
+  config: ThumbShiftConfig,
+  // 判定待ちのキーと押した時刻。文字キー、親指キーの順に押された場合は3打鍵目まで待つ
+  pending: Vec<(KeyCode, u64)>,
+}
+
+impl SimultaneousDetector {
+  pub fn new(config: ThumbShiftConfig) -> Self {
+    SimultaneousDetector {
+      config,
+      pending: vec![],
+    }
+  }
+
+  pub fn is_thumb(&self, key: &KeyCode) -> bool {
+    self.thumb(key).is_some()
+  }
+
+  pub fn push(&mut self, event: &TimedKeyEvent) -> Vec<Stroke> {
+    let mut ret = self.flush_expired(event.timestamp);
+    match event.event {
+      KeyEvents::KeyDown(key) => ret.append(&mut self.key_down(key, event.timestamp)),
+      KeyEvents::KeyUp(key) if self.pending.iter().any(|(k, _)| *k == key) => {
+        ret.append(&mut self.flush())
+      }
+      _ => {}
+    }
+
+    ret
+  }
+
+  // 判定時間を過ぎたキーを確定する
+  pub fn flush_expired(&mut self, now: u64) -> Vec<Stroke> {
+    match self.pending.last() {
+      Some((_, time)) if now.saturating_sub(*time) > self.config.window => self.flush(),
+      _ => vec![],
+    }
+  }
+
+  pub fn flush(&mut self) -> Vec<Stroke> {
+    let ret = match &*self.pending {
+      [] => vec![],
+      [(key, _)] => vec![Stroke::Single(*key)],
+      [(first, _), (second, _), ..] => match (self.thumb(first), self.thumb(second)) {
+        (None, Some(thumb)) => vec![Stroke::Shifted(*first, thumb)],
+        (Some(thumb), None) => vec![Stroke::Shifted(*second, thumb)],
+        _ => vec![Stroke::Single(*first), Stroke::Single(*second)],
+      },
+    };
+    self.pending.clear();
+
+    ret
+  }
+
+  // 判定時間を過ぎたキーはflush_expiredで確定済みなので、残っているキーは判定時間内に押されている
+  fn key_down(&mut self, key: KeyCode, time: u64) -> Vec<Stroke> {
+    match &*self.pending.clone() {
+      [] => {
+        self.pending.push((key, time));
+        vec![]
+      }
+      [(first, _)] if self.is_thumb(first) != self.is_thumb(&key) => {
+        self.pending.push((key, time));
+        // 親指キーが先なら、そのまま文字キーとの同時打鍵で確定する
+        match self.is_thumb(first) {
+          true => self.flush(),
+          false => vec![],
+        }
+      }
+      [(char_key, char_time), (thumb_key, thumb_time)]
+        if !self.is_thumb(char_key) && !self.is_thumb(&key) =>
+      {
+        // 3打鍵目の文字キーのほうが親指キーに近ければ、親指キーは3打鍵目と組み合わせる
+        // 一度に確定するのは1打鍵までとし、組み合わせた打鍵は次のイベントで確定する
+        let thumb = self.thumb(thumb_key).unwrap();
+        match time.saturating_sub(*thumb_time) < thumb_time.saturating_sub(*char_time) {
+          true => {
+            self.pending = vec![(*thumb_key, *thumb_time), (key, time)];
+            vec![Stroke::Single(*char_key)]
+          }
+          false => {
+            self.pending = vec![(key, time)];
+            vec![Stroke::Shifted(*char_key, thumb)]
+          }
+        }
+      }
+      _ => {
+        let ret = self.flush();
+        self.pending.push((key, time));
+
+        ret
+      }
+    }
+  }
+
+  fn thumb(&self, key: &KeyCode) -> Option<Thumb> {
+    match key {
+      _ if *key == self.config.left_thumb => Some(Thumb::Left),
+      _ if *key == self.config.right_thumb => Some(Thumb::Right),
+      _ => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use KeyEvents::*;
+  use Stroke::*;
+
+  fn push_all(detector: &mut SimultaneousDetector, events: &[(KeyEvents, u64)]) -> Vec<Stroke> {
+    events
+      .iter()
+      .flat_map(|(event, timestamp)| detector.push(&TimedKeyEvent::new(*event, *timestamp)))
+      .collect()
+  }
+
+  #[test]
+  fn push() {
+    let a = KeyCode::from("a");
+    let k = KeyCode::from("k");
+    let left = KeyCode::from("space");
+    let right = KeyCode::from("alt");
+    let detector = SimultaneousDetector::new(ThumbShiftConfig::default());
+    let strokes = |events: &[(KeyEvents, u64)]| push_all(&mut detector.clone(), events);
+
+    assert_eq!(strokes(&[(KeyDown(a), 0)]), vec![]);
+    assert_eq!(strokes(&[(KeyDown(a), 0), (KeyUp(a), 30)]), vec![Single(a)]);
+    assert_eq!(
+      strokes(&[(KeyDown(a), 0), (KeyDown(k), 30)]),
+      vec![Single(a)]
+    );
+    assert_eq!(
+      strokes(&[(KeyDown(left), 0), (KeyDown(a), 30)]),
+      vec![Shifted(a, Thumb::Left)]
+    );
+    assert_eq!(
+      strokes(&[(KeyDown(a), 0), (KeyDown(right), 30), (KeyUp(a), 60)]),
+      vec![Shifted(a, Thumb::Right)]
+    );
+    // 判定時間を過ぎたら単独打鍵
+    assert_eq!(
+      strokes(&[(KeyDown(a), 0), (KeyDown(left), 101), (KeyUp(left), 130)]),
+      vec![Single(a), Single(left)]
+    );
+    // 3打鍵目が親指キーに近ければ、親指キーは3打鍵目と組み合わせる
+    assert_eq!(
+      strokes(&[(KeyDown(a), 0), (KeyDown(left), 60), (KeyDown(k), 80)]),
+      vec![Single(a)]
+    );
+    assert_eq!(
+      strokes(&[
+        (KeyDown(a), 0),
+        (KeyDown(left), 60),
+        (KeyDown(k), 80),
+        (KeyUp(k), 90)
+      ]),
+      vec![Single(a), Shifted(k, Thumb::Left)]
+    );
+    assert_eq!(
+      strokes(&[(KeyDown(a), 0), (KeyDown(left), 20), (KeyDown(k), 80)]),
+      vec![Shifted(a, Thumb::Left)]
+    );
+    assert_eq!(
+      strokes(&[
+        (KeyDown(a), 0),
+        (KeyDown(left), 20),
+        (KeyDown(k), 80),
+        (KeyUp(k), 90)
+      ]),
+      vec![Shifted(a, Thumb::Left), Single(k)]
+    );
+  }
+
+  #[test]
+  fn flush_expired() {
+    let a = KeyCode::from("a");
+    let mut detector = SimultaneousDetector::new(ThumbShiftConfig {
+      window: 50,
+      ..ThumbShiftConfig::default()
+    });
+    assert_eq!(push_all(&mut detector, &[(KeyDown(a), 0)]), vec![]);
+    assert_eq!(detector.flush_expired(50), vec![]);
+    assert_eq!(detector.flush_expired(51), vec![Single(a)]);
+    assert_eq!(detector.flush(), vec![]);
+  }
+}
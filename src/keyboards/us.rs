@@ -1,105 +1,74 @@
+use super::layout::{KeyboardLayout, LayoutKey, LayoutKeyboard};
 use super::{KeyCode, Keyboard};
 use std::collections::HashSet;
 
+// 英字以外のキーと、シフトを押したときの文字
+pub const SHIFT: &[(char, char)] = &[
+  ('1', '!'),
+  ('2', '@'),
+  ('3', '#'),
+  ('4', '$'),
+  ('5', '%'),
+  ('6', '^'),
+  ('7', '&'),
+  ('8', '*'),
+  ('9', '('),
+  ('0', ')'),
+  ('-', '_'),
+  ('=', '+'),
+  ('[', '{'),
+  (']', '}'),
+  (';', ':'),
+  ('/', '?'),
+  ('.', '>'),
+  (',', '<'),
+  ('\'', '"'),
+  ('\\', '|'),
+  ('`', '~'),
+];
+
 #[derive(Clone)]
 pub struct US {
-  pressing_keys: HashSet<KeyCode>,
-  last_character: Option<KeyCode>,
+  keyboard: LayoutKeyboard,
 }
 
 impl US {
   pub fn new() -> Self {
     US {
-      pressing_keys: HashSet::new(),
-      last_character: None,
+      keyboard: LayoutKeyboard::new(&US::layout()).unwrap(),
     }
   }
 
-  fn convert(character: char, is_shift_down: bool) -> Option<char> {
-    match is_shift_down {
-      false => Some(character),
-      true => Some(match character {
-        'a' => 'A',
-        'b' => 'B',
-        'c' => 'C',
-        'd' => 'D',
-        'e' => 'E',
-        'f' => 'F',
-        'g' => 'G',
-        'h' => 'H',
-        'i' => 'I',
-        'j' => 'J',
-        'k' => 'K',
-        'l' => 'L',
-        'm' => 'M',
-        'n' => 'N',
-        'o' => 'O',
-        'p' => 'P',
-        'q' => 'Q',
-        'r' => 'R',
-        's' => 'S',
-        't' => 'T',
-        'u' => 'U',
-        'v' => 'V',
-        'w' => 'W',
-        'x' => 'X',
-        'y' => 'Y',
-        'z' => 'Z',
-        '1' => '!',
-        '2' => '@',
-        '3' => '#',
-        '4' => '$',
-        '5' => '%',
-        '6' => '^',
-        '7' => '&',
-        '8' => '*',
-        '9' => '(',
-        '0' => ')',
-        '-' => '_',
-        '=' => '+',
-        '[' => '{',
-        ']' => '}',
-        ';' => ':',
-        '/' => '?',
-        '.' => '>',
-        ',' => '<',
-        '\'' => '"',
-        '\\' => '|',
-        '`' => '~',
-        _ => character,
-      }),
+  pub fn layout() -> KeyboardLayout {
+    let letters = ('a'..='z').map(|c| (c, c.to_ascii_uppercase()));
+    KeyboardLayout {
+      keys: letters
+        .chain(SHIFT.iter().cloned())
+        .map(|(normal, shift)| LayoutKey {
+          key: normal.to_string(),
+          normal,
+          shift: Some(shift),
+        })
+        .collect(),
+      modifiers: vec![],
     }
   }
 }
 
 impl Keyboard for US {
   fn pressing_keys(&self) -> &HashSet<KeyCode> {
-    &self.pressing_keys
+    self.keyboard.pressing_keys()
   }
 
   fn last_character(&self) -> Option<KeyCode> {
-    match &self.last_character? {
-      KeyCode::Printable(c) => Some(KeyCode::Printable(US::convert(
-        *c,
-        self.is_pressing_shift(),
-      )?)),
-      KeyCode::PrintableMeta(meta, c) => Some(KeyCode::PrintableMeta(
-        *meta,
-        US::convert(*c, self.is_pressing_shift())?,
-      )),
-      _ => self.last_character.clone(),
-    }
+    self.keyboard.last_character()
   }
 
   fn key_down(&mut self, key: &KeyCode) {
-    // 絵文字直接入力に対応するため、コードポイントを渡せるようにしたほうがいい？
-    // non USなキーボード時に記号入力がおかしくなりそう
-    // キーボードの抽象化の層が必要では？
-    self.pressing_keys.insert(key.clone());
-    self.last_character = Some(key.clone());
+    self.keyboard.key_down(key);
   }
 
   fn key_up(&mut self, key: &KeyCode) {
-    self.pressing_keys.remove(key);
+    self.keyboard.key_up(key);
   }
 }
@@ -0,0 +1,142 @@
+// Windowsの仮想キーコード(VK_*)
+// OEMキーはkeyboard_typeがJISのときは日本語キーボード、それ以外はUS配列での割り当てとする
+// JIS配列のろキー(VK_OEM_102)は「_」にする
+use super::{KeyCode, Keyboards, MetaKey};
+
+// OEMキーの仮想キーコードはキーボードドライバーの配列で変わるため、配列ごとに読み替える
+pub fn to_key_code(code: u16, keyboard_type: Keyboards) -> Result<KeyCode, &'static str> {
+  match keyboard_type {
+    Keyboards::JIS => to_jis_key_code(code),
+    _ => to_us_key_code(code),
+  }
+}
+
+// 日本語キーボードのドライバーでは刻印の文字の仮想キーコードが届くので、
+// 他のキーコードと同じくUS配列の同じ位置のキーに戻す
+fn to_jis_key_code(code: u16) -> Result<KeyCode, &'static str> {
+  match code {
+    0xba => Ok(KeyCode::from("'")),
+    0xbb => Ok(KeyCode::from(";")),
+    0xc0 => Ok(KeyCode::from("[")),
+    0xdb => Ok(KeyCode::from("]")),
+    0xdc => Ok(KeyCode::from("¥")),
+    0xdd => Ok(KeyCode::from("\\")),
+    0xde => Ok(KeyCode::from("=")),
+    _ => to_us_key_code(code),
+  }
+}
+
+fn to_us_key_code(code: u16) -> Result<KeyCode, &'static str> {
+  match code {
+    0x08 => Ok(KeyCode::from("backspace")),
+    0x09 => Ok(KeyCode::from("tab")),
+    0x0c => Ok(KeyCode::from("clear")),
+    0x0d => Ok(KeyCode::from("enter")),
+    0x10 => Ok(KeyCode::from("shift")),
+    0x11 => Ok(KeyCode::from("ctrl")),
+    0x12 => Ok(KeyCode::from("alt")),
+    0x15 => Ok(KeyCode::from("kana")),
+    0x14 => Ok(KeyCode::from("caps_lock")),
+    0x1b => Ok(KeyCode::from("escape")),
+    0x1c => Ok(KeyCode::from("henkan")),
+    0x1d => Ok(KeyCode::from("muhenkan")),
+    0x20 => Ok(KeyCode::from("space")),
+    0x21 => Ok(KeyCode::from("page_up")),
+    0x22 => Ok(KeyCode::from("page_down")),
+    0x23 => Ok(KeyCode::from("end")),
+    0x24 => Ok(KeyCode::from("home")),
+    0x25 => Ok(KeyCode::from("arrow_left")),
+    0x26 => Ok(KeyCode::from("arrow_up")),
+    0x27 => Ok(KeyCode::from("arrow_right")),
+    0x28 => Ok(KeyCode::from("arrow_down")),
+    0x2e => Ok(KeyCode::from("delete")),
+    0x30..=0x39 => Ok(KeyCode::Printable(code as u8 as char)),
+    0x41..=0x5a => Ok(KeyCode::Printable((code as u8).to_ascii_lowercase() as char)),
+    0x5b => Ok(KeyCode::from("left_command")),
+    0x5c => Ok(KeyCode::from("right_command")),
+    // テンキー
+    0x60..=0x69 => Ok(KeyCode::PrintableMeta(
+      MetaKey::Numpad,
+      (b'0' + (code - 0x60) as u8) as char,
+    )),
+    0x6a => Ok(KeyCode::from("numpad_*")),
+    0x6b => Ok(KeyCode::from("numpad_+")),
+    0x6d => Ok(KeyCode::from("numpad_-")),
+    0x6e => Ok(KeyCode::from("numpad_.")),
+    0x6f => Ok(KeyCode::from("numpad_/")),
+    0x70..=0x87 => Ok(KeyCode::Meta(MetaKey::F((code - 0x6f) as u8))),
+    0xa0 => Ok(KeyCode::from("left_shift")),
+    0xa1 => Ok(KeyCode::from("right_shift")),
+    0xa2 => Ok(KeyCode::from("left_control")),
+    0xa3 => Ok(KeyCode::from("right_control")),
+    0xa4 => Ok(KeyCode::from("left_option")),
+    0xa5 => Ok(KeyCode::from("right_option")),
+    // OEMキー
+    0xba => Ok(KeyCode::from(";")),
+    0xbb => Ok(KeyCode::from("=")),
+    0xbc => Ok(KeyCode::from(",")),
+    0xbd => Ok(KeyCode::from("-")),
+    0xbe => Ok(KeyCode::from(".")),
+    0xbf => Ok(KeyCode::from("/")),
+    0xc0 => Ok(KeyCode::from("`")),
+    0xdb => Ok(KeyCode::from("[")),
+    0xdc => Ok(KeyCode::from("\\")),
+    0xdd => Ok(KeyCode::from("]")),
+    0xde => Ok(KeyCode::from("'")),
+    0xe2 => Ok(KeyCode::from("_")),
+    0xf0 => Ok(KeyCode::from("eisu")),
+    0xf2 => Ok(KeyCode::from("kana")),
+    _ => Err("unknown virtual-key code"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn to_key_code() {
+    assert_eq!(to_us_key_code(0x41), Ok(KeyCode::Printable('a')));
+    assert_eq!(to_us_key_code(0x5a), Ok(KeyCode::Printable('z')));
+    assert_eq!(to_us_key_code(0x31), Ok(KeyCode::Printable('1')));
+    assert_eq!(
+      to_us_key_code(0x60),
+      Ok(KeyCode::PrintableMeta(MetaKey::Numpad, '0'))
+    );
+    assert_eq!(
+      to_us_key_code(0x69),
+      Ok(KeyCode::PrintableMeta(MetaKey::Numpad, '9'))
+    );
+    assert_eq!(
+      to_us_key_code(0x6f),
+      Ok(KeyCode::PrintableMeta(MetaKey::Numpad, '/'))
+    );
+    assert_eq!(to_us_key_code(0x70), Ok(KeyCode::Meta(MetaKey::F(1))));
+    assert_eq!(to_us_key_code(0x24), Ok(KeyCode::Meta(MetaKey::Home)));
+    assert_eq!(to_us_key_code(0xba), Ok(KeyCode::Printable(';')));
+    assert_eq!(to_us_key_code(0xde), Ok(KeyCode::Printable('\'')));
+    assert_eq!(to_us_key_code(0xe2), Ok(KeyCode::Printable('_')));
+    assert_eq!(to_us_key_code(0xa2), Ok(KeyCode::Meta(MetaKey::Ctrl)));
+    assert_eq!(to_us_key_code(0x0d), Ok(KeyCode::from("enter")));
+    assert_eq!(to_us_key_code(0x1c), Ok(KeyCode::Meta(MetaKey::Henkan)));
+    assert!(to_us_key_code(0x88).is_err());
+  }
+
+  #[test]
+  fn to_jis_key_code() {
+    let jis = |code| super::to_key_code(code, Keyboards::JIS);
+    assert_eq!(jis(0x41), Ok(KeyCode::Printable('a')));
+    assert_eq!(jis(0xba), Ok(KeyCode::Printable('\'')));
+    assert_eq!(jis(0xbb), Ok(KeyCode::Printable(';')));
+    assert_eq!(jis(0xc0), Ok(KeyCode::Printable('[')));
+    assert_eq!(jis(0xdb), Ok(KeyCode::Printable(']')));
+    assert_eq!(jis(0xdc), Ok(KeyCode::Printable('¥')));
+    assert_eq!(jis(0xdd), Ok(KeyCode::Printable('\\')));
+    assert_eq!(jis(0xde), Ok(KeyCode::Printable('=')));
+    assert_eq!(jis(0xe2), Ok(KeyCode::Printable('_')));
+    assert_eq!(
+      super::to_key_code(0xba, Keyboards::US),
+      Ok(KeyCode::Printable(';'))
+    );
+  }
+}
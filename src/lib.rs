@@ -19,41 +19,245 @@ mod transformers;
 use std::convert::TryFrom;
 use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
-use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use composition::Composition;
-use keyboards::KeyEvents;
-use transformers::TransformerTypes;
+use dictionary::{CandidateStatistics, DynamicCandidateProvider, DynamicCandidateProviders};
+use keyboards::{KeyCodeSpace, KeyEvents, KeyboardLayout, Keyboards, TimedKeyEvent};
+use transformers::{PunctuationStyle, RomajiRule, RuleTable, TransformerTypes};
 
 pub use composition_result::CompositionResult;
 pub use context::{Context, Contexts};
 pub use dictionary::{Dictionary, DictionaryEntry};
 pub use rskk_config::{KeyConfig, RSKKConfig};
 
+pub type ReloadCallback = extern "C" fn(generation: u64);
+
+// 実行中にFFIなどで変更した設定。設定を読み込み直しても引き継ぐ
+#[derive(Clone, Default)]
+struct Overrides {
+    romaji_rules: Vec<RomajiRule>,
+    punctuation_style: Option<PunctuationStyle>,
+    keyboard_layout: Option<KeyboardLayout>,
+    key_code_space: Option<KeyCodeSpace>,
+}
+
+impl Overrides {
+    fn apply(&self, config: &RSKKConfig) -> RSKKConfig {
+        let mut config = config.clone();
+        config.romaji_rules.extend(self.romaji_rules.iter().cloned());
+        if let Some(style) = self.punctuation_style {
+            config.punctuation_style = style;
+        }
+        if let Some(layout) = &self.keyboard_layout {
+            config.keyboard_type = Keyboards::Custom;
+            config.keyboard_layout = Some(layout.clone());
+        }
+        if let Some(space) = self.key_code_space {
+            config.key_code_space = space;
+        }
+
+        config
+    }
+}
+
+struct Snapshot {
+    // 読み込んだ設定と、それに実行中の変更を反映したもの
+    base_config: Arc<RSKKConfig>,
+    overrides: Overrides,
+    config: Arc<RSKKConfig>,
+    dictionary: Arc<Dictionary>,
+    providers: Arc<DynamicCandidateProviders>,
+    rule_table: Arc<RuleTable>,
+    generation: u64,
+}
+
+impl Snapshot {
+    fn apply_overrides(&mut self) {
+        let config = self.overrides.apply(&self.base_config);
+        self.rule_table = RuleTable::from_config(&config);
+        self.config = Arc::new(config);
+    }
+}
+
 pub struct RSKK {
-    config: Rc<RSKKConfig>,
-    dictionary: Rc<Dictionary>,
+    snapshot: RwLock<Snapshot>,
+    // 使用統計は確定のたびに更新するため、辞書を複製せずにその場で書き換える
+    statistics: Arc<RwLock<CandidateStatistics>>,
     default_composition_type: TransformerTypes,
+    // キー操作で切り替えたときも、以降のCompositionに引き継ぐ
+    is_private_mode: Arc<AtomicBool>,
+    reload_callback: RwLock<Option<ReloadCallback>>,
 }
 
 impl RSKK {
     pub fn new(default_composition_type: TransformerTypes) -> Self {
         RSKK {
-            config: Rc::new(RSKKConfig::default_config()),
-            dictionary: Rc::new(Dictionary::new(set![])),
+            snapshot: RwLock::new(Snapshot {
+                base_config: Arc::new(RSKKConfig::default_config()),
+                overrides: Overrides::default(),
+                config: Arc::new(RSKKConfig::default_config()),
+                dictionary: Arc::new(Dictionary::new(set![])),
+                providers: Arc::new(DynamicCandidateProviders::default()),
+                rule_table: RuleTable::default_rules(),
+                generation: 0,
+            }),
+            statistics: Arc::new(RwLock::new(CandidateStatistics::new())),
             default_composition_type,
+            is_private_mode: Arc::new(AtomicBool::new(false)),
+            reload_callback: RwLock::new(None),
+        }
+    }
+
+    pub fn is_private_mode(&self) -> bool {
+        self.is_private_mode.load(Ordering::SeqCst)
+    }
+
+    pub fn set_private_mode(&self, is_private_mode: bool) {
+        self.is_private_mode.store(is_private_mode, Ordering::SeqCst);
+    }
+
+    // FFI越しにパニックしないよう、ロックが壊れていても中身を使う
+    fn snapshot(&self) -> RwLockReadGuard<Snapshot> {
+        self.snapshot.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn snapshot_mut(&self) -> RwLockWriteGuard<Snapshot> {
+        self.snapshot.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn statistics_mut(&self) -> RwLockWriteGuard<CandidateStatistics> {
+        self.statistics.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn config(&self) -> Arc<RSKKConfig> {
+        self.snapshot().config.clone()
+    }
+
+    pub fn dictionary(&self) -> Arc<Dictionary> {
+        self.snapshot().dictionary.clone()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.snapshot().generation
+    }
+
+    pub fn register_candidate_provider(&self, provider: Arc<dyn DynamicCandidateProvider>) {
+        let mut snapshot = self.snapshot_mut();
+        Arc::make_mut(&mut snapshot.providers).push(provider);
+    }
+
+    pub fn set_reload_callback(&self, callback: Option<ReloadCallback>) {
+        *self.reload_callback.write().unwrap_or_else(|e| e.into_inner()) = callback;
+    }
+
+    // 変換中のCompositionは差し替え前の設定と辞書を保持し続け、
+    // 以降に開始したCompositionから新しいものが使われる
+    pub fn reload(&self, config: Option<RSKKConfig>, dictionary: Option<Dictionary>) -> u64 {
+        self.update(|snapshot| {
+            if let Some(config) = config {
+                snapshot.base_config = Arc::new(config);
+                snapshot.apply_overrides();
+            }
+            if let Some(dictionary) = dictionary {
+                snapshot.dictionary = Arc::new(dictionary);
+            }
+        })
+    }
+
+    fn override_config<F: FnOnce(&mut Overrides)>(&self, f: F) -> u64 {
+        self.update(|snapshot| {
+            f(&mut snapshot.overrides);
+            snapshot.apply_overrides();
+        })
+    }
+
+    fn update<F: FnOnce(&mut Snapshot)>(&self, f: F) -> u64 {
+        let generation = {
+            let mut snapshot = self.snapshot_mut();
+            f(&mut snapshot);
+            snapshot.generation += 1;
+
+            snapshot.generation
+        };
+
+        if let Some(callback) = *self.reload_callback.read().unwrap_or_else(|e| e.into_inner()) {
+            callback(generation);
+        }
+
+        generation
+    }
+
+    pub fn reload_str(&self, config_json: Option<&str>, dic: Option<&str>) -> Result<u64, &str> {
+        let config: Option<RSKKConfig> = match config_json {
+            Some(json) => Some(serde_json::from_str(json).or(Err(""))?),
+            None => None,
+        };
+        if let Some(config) = &config {
+            config.validate().or(Err(""))?;
         }
+
+        Ok(self.reload(config, dic.map(Dictionary::parse)))
+    }
+
+    pub fn swap_dictionary(&self, dictionary: Dictionary) -> u64 {
+        self.reload(None, Some(dictionary))
+    }
+
+    pub fn parse_dictionary(&self, dic: &str) {
+        self.swap_dictionary(Dictionary::parse(dic));
     }
 
-    pub fn parse_dictionary(&mut self, dic: &str) {
-        self.dictionary = Rc::new(Dictionary::parse(dic));
+    pub fn parse_statistics(&self, statistics: &str) {
+        *self.statistics_mut() = CandidateStatistics::parse(statistics);
     }
 
-    pub fn parse_config(&mut self, config_json: &str) -> Result<(), &str> {
-        serde_json::from_str(config_json)
-            .map(|config| self.config = Rc::new(config))
-            .map(|_| ())
-            .or({ Err("") })
+    pub fn statistics(&self) -> String {
+        self.statistics
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .to_string()
+    }
+
+    pub fn record_dictionary_updates(&self, updates: &Vec<DictionaryEntry>) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        let mut statistics = self.statistics_mut();
+
+        updates
+            .iter()
+            .for_each(|entry| statistics.record_usage(entry, timestamp));
+    }
+
+    pub fn parse_config(&self, config_json: &str) -> Result<(), &str> {
+        self.reload_str(Some(config_json), None).map(|_| ())
+    }
+
+    // 設定のromaji_rulesの後ろにルールファイルの内容を追加する
+    // 読み込み直したときは、前に読み込んだルールファイルの内容と置き換える
+    pub fn parse_romaji_rules(&self, rules: &str) -> Result<u64, String> {
+        let rules = RomajiRule::parse_rule_list(rules)?;
+
+        Ok(self.override_config(|overrides| overrides.romaji_rules = rules))
+    }
+
+    pub fn parse_keyboard_layout(&self, layout: &str) -> Result<u64, String> {
+        let layout: KeyboardLayout = serde_json::from_str(layout).map_err(|e| e.to_string())?;
+        layout.validate().map_err(|e| e.to_string())?;
+
+        Ok(self.override_config(|overrides| overrides.keyboard_layout = Some(layout)))
+    }
+
+    pub fn set_punctuation_style(&self, style: PunctuationStyle) -> u64 {
+        self.override_config(|overrides| overrides.punctuation_style = Some(style))
+    }
+
+    pub fn set_key_code_space(&self, space: KeyCodeSpace) -> u64 {
+        self.override_config(|overrides| overrides.key_code_space = Some(space))
     }
 
     pub fn start_composition(&self) -> Composition {
@@ -61,10 +265,32 @@ impl RSKK {
     }
 
     pub fn start_composition_as(&self, composition_type: TransformerTypes) -> Composition {
-        Composition::new(
-            Context::new(self.config.clone(), self.dictionary.clone()),
-            composition_type,
+        let (context, generation) = self.new_context();
+        let mut composition = Composition::new(context, composition_type);
+        composition.set_generation(generation);
+
+        composition
+    }
+
+    // 確定したCompositionの状態を引き継ぎ、読み込み直した設定と辞書で次の入力を始める
+    pub fn next_composition(&self, composition: &Composition) -> Composition {
+        let (context, generation) = self.new_context();
+
+        composition.next_composition_with(context, generation)
+    }
+
+    fn new_context(&self) -> (Context, u64) {
+        let snapshot = self.snapshot();
+        let context = Context::new_with_rule_table(
+            snapshot.config.clone(),
+            snapshot.dictionary.clone(),
+            self.statistics.clone(),
+            snapshot.providers.clone(),
+            snapshot.rule_table.clone(),
         )
+        .with_private_mode(self.is_private_mode.clone());
+
+        (context, snapshot.generation)
     }
 }
 
@@ -75,7 +301,7 @@ pub extern "C" fn rskk_new() -> *mut RSKK {
 
 #[no_mangle]
 pub extern "C" fn rskk_parse_config_json(rskk: *mut RSKK, json: *const c_char) -> bool {
-    match (unsafe { rskk.as_mut() }, unsafe {
+    match (unsafe { rskk.as_ref() }, unsafe {
         CStr::from_ptr(json).to_str()
     }) {
         (Some(rskk), Ok(json)) => Ok((rskk, json)),
@@ -85,9 +311,53 @@ pub extern "C" fn rskk_parse_config_json(rskk: *mut RSKK, json: *const c_char) -
     .map_or_else(|_| false, |_| true)
 }
 
+#[no_mangle]
+pub extern "C" fn rskk_parse_romaji_rules(rskk: *mut RSKK, rules: *const c_char) -> bool {
+    match (unsafe { rskk.as_ref() }, unsafe {
+        CStr::from_ptr(rules).to_str()
+    }) {
+        (Some(rskk), Ok(rules)) => rskk.parse_romaji_rules(rules).is_ok(),
+        _ => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rskk_parse_keyboard_layout(rskk: *mut RSKK, layout: *const c_char) -> bool {
+    match (unsafe { rskk.as_ref() }, unsafe {
+        CStr::from_ptr(layout).to_str()
+    }) {
+        (Some(rskk), Ok(layout)) => rskk.parse_keyboard_layout(layout).is_ok(),
+        _ => false,
+    }
+}
+
+// 0: 、。 1: ，． 2: ,.
+#[no_mangle]
+pub extern "C" fn rskk_set_punctuation_style(rskk: *mut RSKK, style: u8) -> bool {
+    match (unsafe { rskk.as_ref() }, PunctuationStyle::try_from(style)) {
+        (Some(rskk), Ok(style)) => {
+            rskk.set_punctuation_style(style);
+            true
+        }
+        _ => false,
+    }
+}
+
+// 0: macOS 1: evdev 2: keysym 3: Windows
+#[no_mangle]
+pub extern "C" fn rskk_set_key_code_space(rskk: *mut RSKK, space: u8) -> bool {
+    match (unsafe { rskk.as_ref() }, KeyCodeSpace::try_from(space)) {
+        (Some(rskk), Ok(space)) => {
+            rskk.set_key_code_space(space);
+            true
+        }
+        _ => false,
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn rskk_parse_dictionary(rskk: *mut RSKK, dic: *const c_char) -> bool {
-    match (unsafe { rskk.as_mut() }, unsafe {
+    match (unsafe { rskk.as_ref() }, unsafe {
         CStr::from_ptr(dic).to_str()
     }) {
         (Some(rskk), Ok(dic)) => Ok((rskk, dic)),
@@ -97,6 +367,77 @@ pub extern "C" fn rskk_parse_dictionary(rskk: *mut RSKK, dic: *const c_char) ->
     .map_or_else(|_| false, |_| true)
 }
 
+#[no_mangle]
+pub extern "C" fn rskk_reload(
+    rskk: *mut RSKK,
+    config_json: *const c_char,
+    dic: *const c_char,
+) -> bool {
+    let to_str = |s: *const c_char| match s.is_null() {
+        true => Ok(None),
+        false => unsafe { CStr::from_ptr(s).to_str() }.map(|s| Some(s)),
+    };
+
+    match (unsafe { rskk.as_ref() }, to_str(config_json), to_str(dic)) {
+        (Some(rskk), Ok(json), Ok(dic)) => rskk.reload_str(json, dic).is_ok(),
+        _ => false,
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn rskk_set_reload_callback(rskk: *mut RSKK, callback: Option<ReloadCallback>) {
+    unsafe { rskk.as_ref() }.map(|rskk| rskk.set_reload_callback(callback));
+}
+
+#[no_mangle]
+pub extern "C" fn rskk_generation(rskk: *mut RSKK) -> u64 {
+    unsafe { rskk.as_ref() }
+        .map(|rskk| rskk.generation())
+        .unwrap_or(0)
+}
+
+#[no_mangle]
+pub extern "C" fn rskk_composition_generation(composition: *mut Composition) -> u64 {
+    unsafe { composition.as_ref() }
+        .map(|c| c.generation())
+        .unwrap_or(0)
+}
+
+#[no_mangle]
+pub extern "C" fn rskk_parse_statistics(rskk: *mut RSKK, statistics: *const c_char) -> bool {
+    match (unsafe { rskk.as_ref() }, unsafe {
+        CStr::from_ptr(statistics).to_str()
+    }) {
+        (Some(rskk), Ok(statistics)) => Ok((rskk, statistics)),
+        _ => Err(""),
+    }
+    .map(|(rskk, statistics)| rskk.parse_statistics(statistics))
+    .map_or_else(|_| false, |_| true)
+}
+
+#[no_mangle]
+pub extern "C" fn rskk_statistics(rskk: *mut RSKK) -> *mut c_char {
+    let buf = unsafe { rskk.as_ref() }
+        .map(|rskk| rskk.statistics())
+        .unwrap_or("".to_owned());
+
+    CString::new(buf).unwrap().into_raw()
+}
+
+#[no_mangle]
+pub extern "C" fn rskk_record_dictionary_updates(
+    rskk: *mut RSKK,
+    composition: *mut Composition,
+) -> bool {
+    match (unsafe { rskk.as_ref() }, unsafe { composition.as_ref() }) {
+        (Some(rskk), Some(composition)) => {
+            rskk.record_dictionary_updates(composition.result().dictionary_updates());
+            true
+        }
+        _ => false,
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn rskk_free_rskk(raw: *mut RSKK) {
     unsafe { Box::from_raw(raw) };
@@ -121,31 +462,118 @@ pub extern "C" fn rskk_next_composition(
 ) -> *mut Composition {
     unsafe { rskk.as_ref() }
         .map(|rskk| {
-            let tf = unsafe {
-                composition
-                    .as_ref()
-                    .map(|c| c.base_transformer_type())
-                    .unwrap_or(TransformerTypes::Direct)
+            let composition = match unsafe { composition.as_ref() } {
+                Some(composition) => rskk.next_composition(composition),
+                None => rskk.start_composition_as(TransformerTypes::Direct),
             };
-            Box::into_raw(box rskk.start_composition_as(tf))
+
+            Box::into_raw(box composition)
         })
         .unwrap()
 }
 
+#[no_mangle]
+pub extern "C" fn rskk_set_private_mode(rskk: *mut RSKK, is_private_mode: bool) {
+    unsafe { rskk.as_ref() }.map(|rskk| rskk.set_private_mode(is_private_mode));
+}
+
+#[no_mangle]
+pub extern "C" fn rskk_set_composition_private_mode(
+    composition: *mut Composition,
+    is_private_mode: bool,
+) {
+    unsafe { composition.as_mut() }.map(|c| c.set_private_mode(is_private_mode));
+}
+
+#[no_mangle]
+pub extern "C" fn rskk_is_private_mode(composition: *mut Composition) -> bool {
+    unsafe { composition.as_ref() }
+        .map(|c| c.is_private_mode())
+        .unwrap_or(false)
+}
+
+// event_type 1: 押した 2: 離した 3: キーリピート
 #[no_mangle]
 pub extern "C" fn rskk_push_key_event(
     composition: *mut Composition,
     event_type: u16,
     code: u16,
 ) -> bool {
-    let event = KeyEvents::try_from((event_type, code));
-    if event.is_err() {
-        return false;
+    unsafe { composition.as_mut() }
+        .map(|c| push_key_event_as(c, c.key_code_space(), event_type, code as u32))
+        .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn rskk_push_key_event_evdev(
+    composition: *mut Composition,
+    event_type: u16,
+    code: u16,
+) -> bool {
+    unsafe { composition.as_mut() }
+        .map(|c| push_key_event_as(c, KeyCodeSpace::Evdev, event_type, code as u32))
+        .unwrap_or(false)
+}
+
+#[no_mangle]
+pub extern "C" fn rskk_push_key_event_keysym(
+    composition: *mut Composition,
+    event_type: u16,
+    keysym: u32,
+) -> bool {
+    unsafe { composition.as_mut() }
+        .map(|c| push_key_event_as(c, KeyCodeSpace::Keysym, event_type, keysym))
+        .unwrap_or(false)
+}
+
+fn push_key_event_as(
+    composition: &mut Composition,
+    space: KeyCodeSpace,
+    event_type: u16,
+    code: u32,
+) -> bool {
+    match space.to_key_event(event_type, code, composition.keyboard_type()) {
+        Ok(event) => composition.push_key_event_as(space, &event),
+        Err(_) => false,
     }
-    let event = event.unwrap();
+}
+
+// 入力元で確定済みの文字をコードポイントで渡す
+#[no_mangle]
+pub extern "C" fn rskk_push_character(composition: *mut Composition, code_point: u32) -> bool {
+    match (unsafe { composition.as_mut() }, std::char::from_u32(code_point)) {
+        (Some(composition), Some(character)) => {
+            composition.push_key_event(&KeyEvents::Character(character))
+        }
+        _ => false,
+    }
+}
+
+// 親指シフトの同時打鍵を判定するため、押した時刻(ミリ秒)を渡す
+#[no_mangle]
+pub extern "C" fn rskk_push_timed_key_event(
+    composition: *mut Composition,
+    event_type: u16,
+    code: u16,
+    timestamp: u64,
+) -> bool {
+    unsafe { composition.as_mut() }
+        .map(|c| {
+            match c
+                .key_code_space()
+                .to_key_event(event_type, code as u32, c.keyboard_type())
+            {
+                Ok(event) => c.push_timed_key_event(&TimedKeyEvent::new(event, timestamp)),
+                Err(_) => false,
+            }
+        })
+        .unwrap_or(false)
+}
 
+#[no_mangle]
+pub extern "C" fn rskk_flush_expired_keys(composition: *mut Composition, timestamp: u64) -> bool {
     unsafe { composition.as_mut() }
-        .map({ |c| c.push_key_event(&event) })
+        .map(|c| c.flush_expired_keys(timestamp))
         .unwrap_or(false)
 }
 
@@ -167,6 +595,14 @@ pub extern "C" fn rskk_display_string(composition: *mut Composition) -> *mut c_c
     CString::new(buf).unwrap().into_raw()
 }
 
+// display_stringの中のカーソルの位置。UTF-16ではなく文字数で数える
+#[no_mangle]
+pub extern "C" fn rskk_cursor_offset(composition: *mut Composition) -> usize {
+    unsafe { composition.as_ref() }
+        .map(|c| c.cursor_offset())
+        .unwrap_or(0)
+}
+
 #[no_mangle]
 pub extern "C" fn rskk_clear_stopped_buffer(composition: *mut Composition) {
     unsafe { composition.as_mut() }.map(|c| c.clear_stopped_buffer());
@@ -255,6 +691,12 @@ macro_rules! tf {
             crate::transformers::TransformerTypes::Katakana => {
                 Box::new(crate::transformers::KatakanaTransformer::new(conf))
             }
+            crate::transformers::TransformerTypes::EnKatakana => {
+                Box::new(crate::transformers::EnKatakanaTransformer::new(conf))
+            }
+            crate::transformers::TransformerTypes::EmEisu => {
+                Box::new(crate::transformers::EmEisuTransformer::new(conf))
+            }
             crate::transformers::TransformerTypes::Abbr => {
                 Box::new(crate::transformers::AbbrTransformer::new(conf))
             }
@@ -344,10 +786,25 @@ macro_rules! td {
 #[cfg(test)]
 mod lib_tests {
     use super::*;
-    use crate::tests::dummy_context;
+    use crate::dictionary::{Candidate, RankingPolicy};
+    use crate::keyboards::{KeyCode, KeyCombination, KeyCombinations};
+    use crate::tests::{dummy_context, rskk_with};
+    use crate::transformers::RomajiTable;
     use crate::transformers::StoppedReason::*;
+    use std::sync::atomic::AtomicU64;
     use TransformerTypes::*;
 
+    fn assert_send<T: Send>() {}
+    fn assert_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn thread_safety() {
+        assert_send::<Composition>();
+        assert_send::<Box<dyn crate::transformers::Transformable>>();
+        assert_sync::<RSKK>();
+        assert_sync::<Context>();
+    }
+
     #[test]
     fn it_works() {
         let conf = dummy_context();
@@ -373,4 +830,821 @@ mod lib_tests {
         ];
         crate::tests::helpers::TestData::batch(vec);
     }
+
+    fn push_keys(composition: &mut Composition, input: &str) {
+        crate::tests::str_to_key_code_vector(input)
+            .iter()
+            .for_each(|event| {
+                composition.push_key_event(event);
+            });
+    }
+
+    #[test]
+    fn cursor_offset() {
+        let rskk = RSKK::new(Hiragana);
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "Kanji");
+        assert_eq!(composition.cursor_offset(), 4);
+        push_keys(&mut composition, "[arrow_left][arrow_left]");
+        assert_eq!(composition.display_string(), "▽かんじ");
+        assert_eq!(rskk_cursor_offset(&mut composition), 2);
+        push_keys(&mut composition, "[home]");
+        assert_eq!(composition.cursor_offset(), 1);
+        push_keys(&mut composition, "[end]");
+        assert_eq!(composition.cursor_offset(), 4);
+        push_keys(&mut composition, " ");
+        assert_eq!(composition.display_string(), "[登録: かんじ]");
+        assert_eq!(composition.cursor_offset(), 9);
+        assert_eq!(rskk_cursor_offset(std::ptr::null_mut()), 0);
+    }
+
+    #[test]
+    fn record_dictionary_updates() {
+        let rskk = RSKK::new(Hiragana);
+        rskk.parse_dictionary("かんじ /漢字/感じ/");
+
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "Kanji  \n");
+        assert_eq!(composition.stopped_buffer(), "感じ");
+
+        rskk.record_dictionary_updates(composition.result().dictionary_updates());
+        assert!(rskk.statistics().starts_with("かんじ\t感じ\t1\t"));
+
+        let mut config = RSKKConfig::default_config();
+        config.candidate_ranking = RankingPolicy::Frequency;
+        rskk.parse_config(&serde_json::to_string(&config).unwrap())
+            .unwrap();
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "Kanji ");
+        assert_eq!(composition.display_string(), "▼感じ");
+
+        rskk.parse_dictionary("かんじ /漢字/感じ/");
+        assert!(rskk.statistics().starts_with("かんじ\t感じ\t1\t"));
+    }
+
+    #[test]
+    fn private_mode() {
+        let rskk = RSKK::new(Hiragana);
+        rskk.parse_dictionary("かんじ /漢字/感じ/");
+        rskk.set_private_mode(true);
+
+        let mut composition = rskk.start_composition();
+        assert!(composition.is_private_mode());
+        push_keys(&mut composition, "Kanji \n");
+        assert_eq!(composition.stopped_buffer(), "漢字");
+        assert!(composition.result().dictionary_updates().is_empty());
+
+        rskk.record_dictionary_updates(composition.result().dictionary_updates());
+        assert_eq!(rskk.statistics(), "");
+
+        let mut composition = rskk.next_composition(&composition);
+        assert!(composition.is_private_mode());
+        push_keys(&mut composition, "[down:ctrl][down:shift]p[up:shift][up:ctrl]");
+        assert!(!composition.is_private_mode());
+        assert!(!rskk.is_private_mode());
+        assert_eq!(composition.display_string(), "");
+        push_keys(&mut composition, "Kanji \n");
+        assert_eq!(composition.result().dictionary_updates().len(), 1);
+
+        // 確定したときのプライベートモードで、辞書の更新にするかを決める
+        rskk_set_composition_private_mode(&mut composition, true);
+        assert!(rskk.is_private_mode());
+        assert_eq!(composition.result().dictionary_updates().len(), 1);
+
+        let mut composition = rskk.start_composition();
+        assert!(composition.is_private_mode());
+        push_keys(&mut composition, "Kanji ");
+        rskk_set_composition_private_mode(&mut composition, false);
+        assert!(!rskk_is_private_mode(&mut composition));
+        push_keys(&mut composition, "\n");
+        assert_eq!(composition.result().dictionary_updates().len(), 1);
+
+        let mut composition = rskk.start_composition();
+        assert!(!composition.is_private_mode());
+        composition.set_private_mode(true);
+        push_keys(&mut composition, "Kanji \n");
+        assert!(composition.result().dictionary_updates().is_empty());
+        composition.set_private_mode(false);
+        assert!(composition.result().dictionary_updates().is_empty());
+    }
+
+    #[test]
+    fn private_mode_follows_rskk() {
+        let mut rskk = RSKK::new(Hiragana);
+        rskk.parse_dictionary("かんじ /漢字/感じ/");
+
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "Kanji \n");
+        assert_eq!(composition.result().dictionary_updates().len(), 1);
+
+        rskk_set_private_mode(&mut rskk, true);
+        let mut composition = rskk.next_composition(&composition);
+        assert!(composition.is_private_mode());
+        push_keys(&mut composition, "Kanji \n");
+        assert!(composition.result().dictionary_updates().is_empty());
+
+        rskk.set_private_mode(false);
+        let mut composition = rskk.next_composition(&composition);
+        assert!(!composition.is_private_mode());
+        push_keys(&mut composition, "Kanji \n");
+        assert_eq!(composition.result().dictionary_updates().len(), 1);
+    }
+
+    #[test]
+    fn composition_on_worker_thread() {
+        let rskk = RSKK::new(Hiragana);
+        rskk.parse_dictionary("かんじ /漢字/");
+
+        let mut composition = rskk.start_composition();
+        let handle = std::thread::spawn(move || {
+            push_keys(&mut composition, "Kanji \n");
+            composition.stopped_buffer()
+        });
+
+        assert_eq!(handle.join().unwrap(), "漢字");
+    }
+
+    #[test]
+    fn swap_dictionary_while_composing() {
+        let rskk = Arc::new(RSKK::new(Hiragana));
+        rskk.parse_dictionary("かんじ /漢字/");
+
+        let mut live = rskk.start_composition();
+        push_keys(&mut live, "Kanji");
+
+        let swapper = {
+            let rskk = rskk.clone();
+            std::thread::spawn(move || {
+                (0..50).for_each(|i| match i % 2 {
+                    0 => rskk.parse_dictionary("かんじ /感じ/"),
+                    _ => rskk.parse_dictionary("かんじ /漢字/"),
+                })
+            })
+        };
+        let workers = (0..4)
+            .map(|_| {
+                let rskk = rskk.clone();
+                std::thread::spawn(move || {
+                    (0..50)
+                        .map(|_| {
+                            let mut composition = rskk.start_composition();
+                            push_keys(&mut composition, "Kanji \n");
+                            composition.stopped_buffer()
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        swapper.join().unwrap();
+        workers.into_iter().for_each(|worker| {
+            worker
+                .join()
+                .unwrap()
+                .iter()
+                .for_each(|buf| assert!(buf == "漢字" || buf == "感じ", "{}", buf))
+        });
+
+        rskk.swap_dictionary(Dictionary::parse("かんじ /幹事/"));
+        push_keys(&mut live, " \n");
+        assert_eq!(live.stopped_buffer(), "漢字");
+
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "Kanji \n");
+        assert_eq!(composition.stopped_buffer(), "幹事");
+    }
+
+    #[test]
+    fn poisoned_lock() {
+        let rskk = Arc::new(RSKK::new(Hiragana));
+        let poisoner = rskk.clone();
+        let _ = std::thread::spawn(move || {
+            let _snapshot = poisoner.snapshot.write().unwrap();
+            let _statistics = poisoner.statistics.write().unwrap();
+            panic!("poison");
+        })
+        .join();
+        assert!(rskk.snapshot.is_poisoned());
+        assert!(rskk.statistics.is_poisoned());
+
+        let raw = Arc::into_raw(rskk) as *mut RSKK;
+        let dic = CString::new("かんじ /漢字/").unwrap();
+        assert!(rskk_parse_dictionary(raw, dic.as_ptr()));
+        let composition = rskk_start_composition(raw);
+        let mut composition = unsafe { Box::from_raw(composition) };
+        push_keys(&mut composition, "[down:ctrl]j[up:ctrl]Kanji \n");
+        assert_eq!(composition.stopped_buffer(), "漢字");
+        assert!(rskk_record_dictionary_updates(raw, &mut *composition));
+        assert!(unsafe { raw.as_ref() }
+            .unwrap()
+            .statistics()
+            .starts_with("かんじ\t漢字\t1\t"));
+        drop(unsafe { Arc::from_raw(raw) });
+    }
+
+    static RELOADED_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+    extern "C" fn on_reload(generation: u64) {
+        RELOADED_GENERATION.store(generation, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn reload() {
+        let rskk = RSKK::new(Hiragana);
+        rskk.parse_dictionary("かんじ /漢字/");
+        rskk.set_reload_callback(Some(on_reload));
+        let generation = rskk.generation();
+
+        let mut live = rskk.start_composition();
+        push_keys(&mut live, "Kanji");
+        assert_eq!(live.generation(), generation);
+
+        let mut config = RSKKConfig::default_config();
+        config.candidate_ranking = RankingPolicy::Frequency;
+        let config = serde_json::to_string(&config).unwrap();
+        assert!(rskk.reload_str(Some("{"), Some("かんじ /幹事/")).is_err());
+        assert_eq!(rskk.generation(), generation);
+
+        let reloaded = rskk.reload_str(Some(&config), Some("かんじ /幹事/"));
+        assert_eq!(reloaded, Ok(generation + 1));
+        assert_eq!(RELOADED_GENERATION.load(Ordering::SeqCst), generation + 1);
+        assert_eq!(rskk.config().candidate_ranking, RankingPolicy::Frequency);
+
+        push_keys(&mut live, " \n");
+        assert_eq!(live.stopped_buffer(), "漢字");
+
+        let next = rskk_next_composition(&rskk as *const _ as *mut _, &mut live);
+        let next = unsafe { Box::from_raw(next) };
+        assert_eq!(next.generation(), generation + 1);
+
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "Kanji \n");
+        assert_eq!(composition.stopped_buffer(), "幹事");
+    }
+
+    #[test]
+    fn reload_keeps_overrides() {
+        let rskk = RSKK::new(Hiragana);
+        let config = serde_json::to_string(&RSKKConfig::default_config()).unwrap();
+        let converted = |input: &str| {
+            let mut composition = rskk.start_composition();
+            push_keys(&mut composition, input);
+            composition.stopped_buffer()
+        };
+
+        assert!(rskk.parse_romaji_rules("(\"tt\" nil \"っ\")\n").is_ok());
+        rskk.parse_config(&config).unwrap();
+        assert_eq!(converted("tt"), "っ");
+
+        rskk.set_punctuation_style(PunctuationStyle::Ascii);
+        rskk.parse_config(&config).unwrap();
+        assert_eq!(converted(","), ",");
+
+        assert!(rskk
+            .parse_keyboard_layout(
+                r#"{ "keys": [{ "key": "a", "normal": "k" }, { "key": "q", "normal": "a" }] }"#
+            )
+            .is_ok());
+        rskk.parse_config(&config).unwrap();
+        assert_eq!(converted("aq"), "か");
+
+        rskk.set_key_code_space(KeyCodeSpace::Evdev);
+        rskk.parse_config(&config).unwrap();
+        assert_eq!(rskk.config().key_code_space, KeyCodeSpace::Evdev);
+        assert_eq!(rskk.config().keyboard_type, Keyboards::Custom);
+        assert_eq!(rskk.config().romaji_rules.len(), 1);
+    }
+
+    #[test]
+    fn next_composition_after_reload() {
+        let rskk = RSKK::new(Hiragana);
+        rskk.parse_dictionary("かんじ /漢字/");
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "Kanji \n");
+        assert_eq!(composition.stopped_buffer(), "漢字");
+
+        rskk.parse_dictionary("かんじ /幹事/");
+        let mut next = rskk.next_composition(&composition);
+        assert_eq!(next.generation(), rskk.generation());
+        push_keys(&mut next, "Kanji \n");
+        assert_eq!(next.stopped_buffer(), "幹事");
+
+        let next = rskk_next_composition(&rskk as *const _ as *mut _, &mut composition);
+        let mut next = unsafe { Box::from_raw(next) };
+        assert_eq!(next.generation(), rskk.generation());
+        push_keys(&mut next, "Kanji \n");
+        assert_eq!(next.stopped_buffer(), "幹事");
+    }
+
+    #[test]
+    fn dynamic_candidates() {
+        struct Greeting;
+        impl DynamicCandidateProvider for Greeting {
+            fn candidates(&self, read: &str, _: &RSKKConfig) -> Vec<Candidate> {
+                match read {
+                    "あいさつ" => vec![Candidate::new("こんにちは", None)],
+                    _ => vec![],
+                }
+            }
+        }
+
+        let rskk = RSKK::new(Hiragana);
+        rskk.parse_dictionary("あいさつ /挨拶/");
+        rskk.register_candidate_provider(Arc::new(Greeting));
+
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "Aisatsu  \n");
+        assert_eq!(composition.stopped_buffer(), "こんにちは");
+        assert_eq!(composition.result().dictionary_updates(), &vec![]);
+
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "Aisatsu \n");
+        assert_eq!(composition.stopped_buffer(), "挨拶");
+        assert_eq!(composition.result().dictionary_updates().len(), 1);
+
+        // 確定までに生成し直すと変わる候補も学習しない
+        struct Counter(AtomicU64);
+        impl DynamicCandidateProvider for Counter {
+            fn candidates(&self, read: &str, _: &RSKKConfig) -> Vec<Candidate> {
+                match read {
+                    "かうんと" => {
+                        let count = self.0.fetch_add(1, Ordering::SeqCst);
+                        vec![Candidate::new(count.to_string(), None)]
+                    }
+                    _ => vec![],
+                }
+            }
+        }
+        rskk.register_candidate_provider(Arc::new(Counter(AtomicU64::new(0))));
+
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "Kaunto \n");
+        assert_eq!(composition.stopped_buffer(), "0");
+        assert_eq!(composition.result().dictionary_updates(), &vec![]);
+    }
+
+    #[test]
+    fn romaji_rules() {
+        let mut config = RSKKConfig::default_config();
+        config.romaji_rules = vec![RomajiRule::new("z,", Some("，"), "")];
+        let rskk = rskk_with(config);
+
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "z,");
+        assert_eq!(composition.stopped_buffer(), "，");
+
+        assert!(rskk.parse_romaji_rules("(\"tt\" nil \"っ\"\n").is_err());
+        assert!(rskk
+            .parse_romaji_rules(";; personal\n(\"tt\" nil \"っ\")\n(\"zh\" nil nil)\n")
+            .is_ok());
+
+        let converted = |input: &str| {
+            let mut composition = rskk.start_composition();
+            push_keys(&mut composition, input);
+            composition.stopped_buffer()
+        };
+        assert_eq!(converted("z,"), "，");
+        assert_eq!(converted("tt"), "っ");
+        assert_eq!(converted("zh"), "z");
+
+        assert!(rskk
+            .parse_romaji_rules("'((\"tt\" nil \"っ\")\n  (\"zh\" nil nil))\n")
+            .is_ok());
+        assert_eq!(rskk.config().romaji_rules.len(), 3);
+
+        assert!(rskk.parse_romaji_rules("(\"hh\" nil \"ふ\")").is_ok());
+        assert_eq!(rskk.config().romaji_rules.len(), 2);
+        assert_eq!(converted("hh"), "ふ");
+        assert_eq!(converted("zh"), "←");
+    }
+
+    #[test]
+    fn romaji_table() {
+        let mut config = RSKKConfig::default_config();
+        config.romaji_table = RomajiTable::Azik;
+        let rskk = rskk_with(config);
+        rskk.parse_dictionary("かんじ /漢字/");
+
+        let converted = |input: &str| {
+            let mut composition = rskk.start_composition();
+            push_keys(&mut composition, input);
+            composition.stopped_buffer()
+        };
+        assert_eq!(converted("kz"), "かん");
+        assert_eq!(converted("q"), "ん");
+        assert_eq!(converted(";"), "っ");
+        assert_eq!(converted("Kzji \n"), "漢字");
+        assert_eq!(converted("Kzjiq"), "");
+
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "[[]");
+        assert_eq!(composition.transformer_type(), Katakana);
+        push_keys(&mut composition, "ka");
+        assert_eq!(composition.stopped_buffer(), "カ");
+
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "Kzji[[]");
+        assert_eq!(composition.stopped_buffer(), "カンジ");
+    }
+
+    #[test]
+    fn punctuation_style() {
+        let raw = Box::into_raw(Box::new(RSKK::new(Hiragana)));
+        let rskk = unsafe { raw.as_ref() }.unwrap();
+        let converted = |input: &str| {
+            let mut composition = rskk.start_composition();
+            push_keys(&mut composition, input);
+            composition.stopped_buffer()
+        };
+        assert_eq!(converted(","), "、");
+
+        assert!(rskk_set_punctuation_style(raw, 1));
+        assert_eq!(converted(","), "，");
+        assert_eq!(converted("."), "．");
+
+        assert!(rskk_set_punctuation_style(raw, 2));
+        assert_eq!(converted(","), ",");
+        assert_eq!(converted("[[]"), "[");
+        assert_eq!(rskk.config().punctuation_style, PunctuationStyle::Ascii);
+
+        assert!(!rskk_set_punctuation_style(raw, 3));
+        rskk_free_rskk(raw);
+    }
+
+    #[test]
+    fn nicola() {
+        use keyboards::KeyCode;
+        use KeyEvents::*;
+
+        let mut config = RSKKConfig::default_config();
+        config.input_method = transformers::InputMethod::Nicola;
+        let rskk = rskk_with(config);
+        rskk.parse_dictionary("かんじ /漢字/");
+
+        let key = |name: &str| KeyCode::from(name);
+        let push = |composition: &mut Composition, events: &[(KeyEvents, u64)]| {
+            events.iter().for_each(|(event, timestamp)| {
+                composition.push_timed_key_event(&TimedKeyEvent::new(*event, *timestamp));
+            });
+        };
+        let converted = |events: &[(KeyEvents, u64)]| {
+            let mut composition = rskk.start_composition();
+            push(&mut composition, events);
+            composition.stopped_buffer()
+        };
+
+        assert_eq!(converted(&[(KeyDown(key("w")), 0)]), "");
+        assert_eq!(
+            converted(&[(KeyDown(key("w")), 0), (KeyUp(key("w")), 30)]),
+            "か"
+        );
+        assert_eq!(
+            converted(&[(KeyDown(key("space")), 0), (KeyDown(key("w")), 20)]),
+            "え"
+        );
+        assert_eq!(
+            converted(&[
+                (KeyDown(key("w")), 0),
+                (KeyDown(key("alt")), 30),
+                (KeyUp(key("w")), 50)
+            ]),
+            "が"
+        );
+        assert_eq!(
+            converted(&[(KeyDown(key("space")), 0), (KeyUp(key("space")), 40)]),
+            " "
+        );
+
+        // 英数では同時打鍵を判定せず、スペースは入力元に返す
+        let mut composition = rskk.start_composition_as(Direct);
+        assert!(!composition.push_timed_key_event(&TimedKeyEvent::new(KeyDown(key("space")), 0)));
+        push(
+            &mut composition,
+            &[
+                (KeyDown(key("w")), 20),
+                (KeyUp(key("space")), 30),
+                (KeyUp(key("w")), 40),
+            ],
+        );
+        assert_eq!(composition.stopped_buffer(), "w");
+
+        let mut composition = rskk.start_composition();
+        push(&mut composition, &[(KeyDown(key("w")), 0)]);
+        assert!(!composition.flush_expired_keys(100));
+        assert!(composition.flush_expired_keys(101));
+        assert_eq!(composition.stopped_buffer(), "か");
+
+        // 判定待ちのキーは次の入力に引き継ぐ
+        let mut composition = rskk.start_composition();
+        push(
+            &mut composition,
+            &[(KeyDown(key("w")), 0), (KeyDown(key("s")), 30)],
+        );
+        assert_eq!(composition.stopped_buffer(), "か");
+        let mut composition = rskk.next_composition(&composition);
+        push(&mut composition, &[(KeyUp(key("s")), 50)]);
+        assert_eq!(composition.stopped_buffer(), "し");
+
+        // シフトを押した文字キーは同時打鍵の判定をせずに▽を始める
+        let mut composition = rskk.start_composition();
+        push(
+            &mut composition,
+            &[
+                (KeyDown(key("shift")), 0),
+                (KeyDown(key("w")), 10),
+                (KeyUp(key("w")), 20),
+                (KeyUp(key("shift")), 30),
+                (KeyDown(key(";")), 200),
+                (KeyUp(key(";")), 220),
+                (KeyDown(key("alt")), 400),
+                (KeyDown(key("s")), 420),
+            ],
+        );
+        assert_eq!(composition.display_string(), "▽かんじ");
+        push(
+            &mut composition,
+            &[(KeyDown(key("space")), 600), (KeyUp(key("space")), 620)],
+        );
+        assert_eq!(composition.display_string(), "▼漢字");
+    }
+
+    #[test]
+    fn jis_keyboard() {
+        let mut config = RSKKConfig::default_config();
+        config.keyboard_type = keyboards::Keyboards::JIS;
+        let rskk = rskk_with(config);
+
+        let converted = |composition_type, input: &str| {
+            let mut composition = rskk.start_composition_as(composition_type);
+            push_keys(&mut composition, input);
+            composition.stopped_buffer()
+        };
+        assert_eq!(converted(Direct, "[[]"), "@");
+        assert_eq!(converted(Direct, "[down:shift]2"), "\"");
+        assert_eq!(converted(Direct, "[down:shift]'"), "*");
+        assert_eq!(converted(Direct, "¥"), "¥");
+        assert_eq!(converted(Hiragana, "]"), "「");
+        assert_eq!(converted(Hiragana, "="), "^");
+
+        let mut composition = rskk.start_composition_as(Hiragana);
+        push_keys(&mut composition, "[eisu]");
+        assert_eq!(composition.transformer_type(), Direct);
+        push_keys(&mut composition, "[kana]");
+        assert_eq!(composition.transformer_type(), Hiragana);
+    }
+
+    #[test]
+    fn dvorak_keyboard() {
+        let mut config = RSKKConfig::default_config();
+        config.keyboard_type = keyboards::Keyboards::Dvorak;
+        let rskk = rskk_with(config);
+
+        let mut composition = rskk.start_composition_as(Direct);
+        push_keys(&mut composition, "[down:ctrl]c[up:ctrl]");
+        assert_eq!(composition.transformer_type(), Hiragana);
+        push_keys(&mut composition, "jd");
+        assert_eq!(composition.stopped_buffer(), "へ");
+    }
+
+    #[test]
+    fn keyboard_layout() {
+        let rskk = RSKK::new(Hiragana);
+        assert!(rskk.parse_keyboard_layout(r#"{ "keys": [] }"#).is_err());
+        assert!(rskk
+            .parse_keyboard_layout(
+                r#"{
+                  "keys": [{ "key": "a", "normal": "k", "shift": "K" }, { "key": "s", "normal": "a" }],
+                  "modifiers": [{ "key": "kana", "modifier": "ctrl" }]
+                }"#
+            )
+            .is_ok());
+
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "as");
+        assert_eq!(composition.stopped_buffer(), "か");
+
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "[down:shift]as");
+        assert_eq!(composition.display_string(), "▽か");
+
+        let mut composition = rskk.start_composition_as(Direct);
+        push_keys(&mut composition, "[down:kana]j[up:kana]");
+        assert_eq!(composition.transformer_type(), Hiragana);
+
+        let mut config = RSKKConfig::default_config();
+        config.keyboard_layout = Some(KeyboardLayout::default());
+        assert!(rskk
+            .parse_config(&serde_json::to_string(&config).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn key_code_space() {
+        let rskk = RSKK::new(Hiragana);
+        let push = |composition: &mut Composition, codes: &[u16]| {
+            codes.iter().for_each(|code| {
+                rskk_push_key_event(composition, 1, *code);
+                rskk_push_key_event(composition, 2, *code);
+            })
+        };
+
+        // macOS: k a
+        let mut composition = rskk.start_composition();
+        push(&mut composition, &[40, 0]);
+        assert_eq!(composition.stopped_buffer(), "か");
+
+        rskk.set_key_code_space(KeyCodeSpace::Evdev);
+        let mut composition = rskk.start_composition();
+        push(&mut composition, &[37, 30]);
+        assert_eq!(composition.stopped_buffer(), "か");
+
+        let mut composition = rskk.start_composition();
+        rskk_push_key_event_keysym(&mut composition, 1, 0xffe1);
+        assert!(rskk_push_key_event_keysym(&mut composition, 1, 0x4b));
+        rskk_push_key_event_keysym(&mut composition, 2, 0x4b);
+        rskk_push_key_event_keysym(&mut composition, 2, 0xffe1);
+        assert!(rskk_push_key_event_keysym(&mut composition, 1, 0x61));
+        assert_eq!(composition.display_string(), "▽か");
+        assert!(!rskk_push_key_event_keysym(&mut composition, 1, 0x3042));
+        // シフトを先に離しても、押した記号のキーを押したままにしない
+        rskk_push_key_event_keysym(&mut composition, 1, 0xffe1);
+        rskk_push_key_event_keysym(&mut composition, 1, 0x21);
+        rskk_push_key_event_keysym(&mut composition, 2, 0xffe1);
+        rskk_push_key_event_keysym(&mut composition, 2, 0x31);
+        assert_eq!(composition.display_string(), "▽か！");
+        rskk_push_key_event_keysym(&mut composition, 1, 0xffe3);
+        assert!(rskk_push_key_event_keysym(&mut composition, 1, 0x67));
+        assert_eq!(composition.display_string(), "");
+
+        rskk.set_key_code_space(KeyCodeSpace::Windows);
+        let mut composition = rskk.start_composition();
+        push(&mut composition, &[0x4b, 0x41]);
+        assert_eq!(composition.stopped_buffer(), "か");
+
+        // keysymは配列を反映した文字なので、JIS配列でも読み替えない
+        let mut config = RSKKConfig::default_config();
+        config.keyboard_type = keyboards::Keyboards::JIS;
+        let jis = rskk_with(config);
+        let push_keysym = |composition: &mut Composition, keysyms: &[u32]| {
+            keysyms.iter().for_each(|keysym| {
+                rskk_push_key_event_keysym(composition, 1, *keysym);
+                rskk_push_key_event_keysym(composition, 2, *keysym);
+            })
+        };
+        let mut composition = jis.start_composition_as(Direct);
+        rskk_push_key_event_keysym(&mut composition, 1, 0xffe1);
+        push_keysym(&mut composition, &[0x40]);
+        assert_eq!(composition.stopped_buffer(), "@");
+        let mut composition = jis.start_composition_as(Direct);
+        push_keysym(&mut composition, &[0x5e]);
+        assert_eq!(composition.stopped_buffer(), "^");
+        let mut composition = jis.start_composition();
+        push_keysym(&mut composition, &[0x6b, 0x61]);
+        assert_eq!(composition.stopped_buffer(), "か");
+
+        jis.set_key_code_space(KeyCodeSpace::Keysym);
+        let mut composition = jis.start_composition_as(Direct);
+        rskk_push_key_event(&mut composition, 1, 0xffe1);
+        rskk_push_key_event(&mut composition, 1, 0x2a);
+        assert_eq!(composition.stopped_buffer(), "*");
+
+        // JIS配列ではVK_OEM_3が「@」のキー、VK_OEM_7が「^」のキー
+        let mut composition = rskk.start_composition_as(Direct);
+        push(&mut composition, &[0xc0]);
+        assert_eq!(composition.stopped_buffer(), "`");
+
+        let mut config = RSKKConfig::default_config();
+        config.keyboard_type = keyboards::Keyboards::JIS;
+        config.key_code_space = KeyCodeSpace::Windows;
+        let rskk = rskk_with(config);
+        let mut composition = rskk.start_composition_as(Direct);
+        push(&mut composition, &[0xc0]);
+        assert_eq!(composition.stopped_buffer(), "@");
+        let mut composition = rskk.start_composition_as(Direct);
+        push(&mut composition, &[0xde]);
+        assert_eq!(composition.stopped_buffer(), "^");
+        let mut composition = rskk.start_composition_as(Direct);
+        push(&mut composition, &[0xba]);
+        assert_eq!(composition.stopped_buffer(), ":");
+    }
+
+    #[test]
+    fn key_repeat() {
+        let rskk = RSKK::new(Hiragana);
+        let mut composition = rskk.start_composition();
+        // shift + k, a を押したままリピート
+        rskk_push_key_event(&mut composition, 1, 56);
+        rskk_push_key_event(&mut composition, 1, 40);
+        rskk_push_key_event(&mut composition, 2, 40);
+        rskk_push_key_event(&mut composition, 2, 56);
+        assert!(rskk_push_key_event(&mut composition, 1, 0));
+        assert!(rskk_push_key_event(&mut composition, 3, 0));
+        rskk_push_key_event(&mut composition, 2, 0);
+        assert_eq!(composition.display_string(), "▽かあ");
+        assert!(!rskk_push_key_event(&mut composition, 4, 0));
+    }
+
+    #[test]
+    fn character() {
+        let rskk = RSKK::new(Hiragana);
+        let mut composition = rskk.start_composition();
+        assert!(rskk_push_character(&mut composition, 'é' as u32));
+        assert_eq!(composition.stopped_buffer(), "é");
+
+        let mut composition = rskk.start_composition_as(Direct);
+        assert!(rskk_push_character(&mut composition, 0x1f600));
+        assert_eq!(composition.stopped_buffer(), "😀");
+        assert!(!rskk_push_character(&mut composition, 0xd800));
+    }
+
+    #[test]
+    fn meta_keys() {
+        let rskk = RSKK::new(Hiragana);
+        let mut composition = rskk.start_composition();
+        // Home, End, F1 は入力元に返す
+        assert!(!rskk_push_key_event(&mut composition, 1, 115));
+        assert!(!rskk_push_key_event(&mut composition, 1, 119));
+        assert!(!rskk_push_key_event(&mut composition, 1, 122));
+        assert_eq!(composition.display_string(), "");
+
+        // テンキー
+        assert!(rskk_push_key_event(&mut composition, 1, 83));
+        assert_eq!(composition.stopped_buffer(), "1");
+
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "Kanji[home]");
+        assert!(rskk_push_key_event(&mut composition, 1, 119));
+        push_keys(&mut composition, "i");
+        assert_eq!(composition.display_string(), "▽かんじい");
+    }
+
+    #[test]
+    fn sticky_shift() {
+        let mut config = RSKKConfig::default_config();
+        config.is_enable_sticky_shift = true;
+        let rskk = rskk_with(config);
+
+        let converted = |composition_type, input: &str| {
+            let mut composition = rskk.start_composition_as(composition_type);
+            push_keys(&mut composition, input);
+            composition.stopped_buffer()
+        };
+        assert_eq!(converted(Direct, ";a"), "A");
+        assert_eq!(converted(Direct, ";;"), ";");
+        assert_eq!(converted(Direct, ";2"), "@");
+
+        let mut composition = rskk.start_composition_as(Hiragana);
+        push_keys(&mut composition, ";l");
+        assert_eq!(composition.transformer_type(), EmEisu);
+
+        let displayed = |composition_type, input: &str| {
+            let mut composition = rskk.start_composition_as(composition_type);
+            push_keys(&mut composition, input);
+            composition.display_string()
+        };
+        assert_eq!(displayed(Hiragana, ";ka;ku"), "[登録: か*く]");
+        assert_eq!(displayed(Katakana, ";ka"), "▽カ");
+        assert_eq!(displayed(Katakana, ";ka;ku"), "[登録: カ*ク]");
+        assert_eq!(converted(Katakana, ";;"), ";");
+        assert_eq!(displayed(EnKatakana, ";ka"), "▽ｶ");
+        assert_eq!(converted(EnKatakana, ";;"), ";");
+
+        // AZIKでは「;」が「っ」だが、スティッキーシフトを有効にしたときはスティッキーシフトにする
+        let mut config = RSKKConfig::default_config();
+        config.is_enable_sticky_shift = true;
+        config.romaji_table = RomajiTable::Azik;
+        let azik = rskk_with(config);
+        let mut composition = azik.start_composition();
+        push_keys(&mut composition, ";kz");
+        assert_eq!(composition.display_string(), "▽かん");
+        let mut composition = azik.start_composition();
+        push_keys(&mut composition, ";;");
+        assert_eq!(composition.stopped_buffer(), ";");
+
+        let mut config = RSKKConfig::default_config();
+        config.romaji_table = RomajiTable::Azik;
+        let azik = rskk_with(config);
+        let mut composition = azik.start_composition();
+        push_keys(&mut composition, ";");
+        assert_eq!(composition.stopped_buffer(), "っ");
+    }
+
+    #[test]
+    fn control_keys() {
+        let rskk = RSKK::new(Hiragana);
+        let mut composition = rskk.start_composition();
+        // 入力中でなければ、C-hは入力元に返す
+        push_keys(&mut composition, "[down:ctrl]");
+        assert!(!rskk_push_key_event(&mut composition, 1, 4));
+        rskk_push_key_event(&mut composition, 2, 4);
+        push_keys(&mut composition, "[up:ctrl]Kannji[down:ctrl]h");
+        assert_eq!(composition.display_string(), "▽かん");
+
+        let mut config = RSKKConfig::default_config();
+        config.key_config.cancel = combos![combo![KeyCode::from("ctrl"), KeyCode::from("c")]];
+        let rskk = rskk_with(config);
+        let mut composition = rskk.start_composition();
+        push_keys(&mut composition, "Kanji[down:ctrl]c");
+        assert_eq!(composition.transformer_type(), Stopped(Canceled));
+    }
 }
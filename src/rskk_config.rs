@@ -1,7 +1,13 @@
 use serde::{Deserialize, Serialize};
 
-use crate::keyboards::{KeyCode, KeyCombination, KeyCombinations, Keyboards};
-use crate::transformers::TransformerTypes;
+use crate::dictionary::{DateTimeConfig, RankingPolicy};
+use crate::keyboards::{
+  keysym, KeyCode, KeyCodeSpace, KeyCombination, KeyCombinations, Keyboard, KeyboardLayout,
+  Keyboards, LayoutError, LayoutKeyboard, ThumbShiftConfig,
+};
+use crate::transformers::{
+  InputMethod, PunctuationStyle, RomajiRule, RomajiTable, TransformerTypes,
+};
 use crate::{combo, combos};
 use std::collections::HashSet;
 
@@ -9,7 +15,33 @@ use std::collections::HashSet;
 pub struct RSKKConfig {
   pub keyboard_type: Keyboards,
   pub key_config: KeyConfig,
+  // 有効にすると、AZIKの「;」(っ)よりもsticky_keyとしての動作を優先する
   pub is_enable_sticky_shift: bool,
+  // 変換や確定のときに、末尾の「n」を「ん」として出力する
+  #[serde(default = "RSKKConfig::default_trailing_n_flush")]
+  pub is_enable_trailing_n_flush: bool,
+  #[serde(default)]
+  pub candidate_ranking: RankingPolicy,
+  #[serde(default)]
+  pub date_time: DateTimeConfig,
+  #[serde(default)]
+  pub romaji_table: RomajiTable,
+  #[serde(default)]
+  pub romaji_rules: Vec<RomajiRule>,
+  #[serde(default)]
+  pub punctuation_style: PunctuationStyle,
+  #[serde(default)]
+  pub input_method: InputMethod,
+  // かな入力や親指シフトでは文字キーがかなに直接対応するため、こちらのキー設定を使う
+  #[serde(default = "KeyConfig::default_kana_config")]
+  pub kana_key_config: KeyConfig,
+  #[serde(default)]
+  pub thumb_shift: ThumbShiftConfig,
+  // keyboard_typeがCustomのときに使う配列
+  #[serde(default)]
+  pub keyboard_layout: Option<KeyboardLayout>,
+  #[serde(default)]
+  pub key_code_space: KeyCodeSpace,
 }
 
 impl RSKKConfig {
@@ -18,11 +50,58 @@ impl RSKKConfig {
       keyboard_type: Keyboards::US,
       key_config: KeyConfig::default_config(),
       is_enable_sticky_shift: false,
+      is_enable_trailing_n_flush: Self::default_trailing_n_flush(),
+      candidate_ranking: RankingPolicy::Dictionary,
+      date_time: DateTimeConfig::default(),
+      romaji_table: RomajiTable::Standard,
+      romaji_rules: vec![],
+      punctuation_style: PunctuationStyle::Japanese,
+      input_method: InputMethod::Romaji,
+      kana_key_config: KeyConfig::default_kana_config(),
+      thumb_shift: ThumbShiftConfig::default(),
+      keyboard_layout: None,
+      key_code_space: KeyCodeSpace::MacOS,
     }
   }
 
   pub fn key_config(&self) -> &KeyConfig {
-    &self.key_config
+    match self.input_method {
+      InputMethod::Romaji => &self.key_config,
+      _ => &self.kana_key_config,
+    }
+  }
+
+  pub fn to_keyboard(&self) -> Box<dyn Keyboard> {
+    self.to_keyboard_for(self.key_code_space)
+  }
+
+  // 配列が読み込めない場合はUS配列にする
+  // keysymは配列を反映した文字で届くため、配列による読み替えはしない
+  pub fn to_keyboard_for(&self, key_code_space: KeyCodeSpace) -> Box<dyn Keyboard> {
+    match (key_code_space, &self.keyboard_type, &self.keyboard_layout) {
+      (KeyCodeSpace::Keysym, _, _) => keysym::to_keyboard(),
+      (_, Keyboards::Custom, Some(layout)) => match LayoutKeyboard::new(layout) {
+        Ok(keyboard) => Box::new(keyboard),
+        Err(_) => Keyboards::US.to_keyboard(),
+      },
+      (_, keyboard_type, _) => keyboard_type.to_keyboard(),
+    }
+  }
+
+  pub fn validate(&self) -> Result<(), LayoutError> {
+    match &self.keyboard_layout {
+      Some(layout) => layout.validate(),
+      None => Ok(()),
+    }
+  }
+
+  // かな入力では濁点を待っているかなも、変換や確定のときに出力する
+  pub fn is_flush_pending_input(&self) -> bool {
+    self.is_enable_trailing_n_flush || self.input_method != InputMethod::Romaji
+  }
+
+  fn default_trailing_n_flush() -> bool {
+    true
   }
 }
 
@@ -36,58 +115,139 @@ pub struct KeyConfig {
   pub enter_abbr_transformer: KeyCombinations,
   pub enter_direct_transformer: KeyCombinations,
   pub sticky_key: KeyCombinations,
+  #[serde(default = "KeyConfig::default_toggle_private_mode")]
+  pub toggle_private_mode: KeyCombinations,
+  #[serde(default = "KeyConfig::default_cancel")]
+  pub cancel: KeyCombinations,
+  #[serde(default = "KeyConfig::default_backspace")]
+  pub backspace: KeyCombinations,
+  #[serde(default = "KeyConfig::default_next_candidate")]
+  pub next_candidate: KeyCombinations,
+  // next_candidateとprev_candidateは、候補の選択中だけ使う
+  #[serde(default = "KeyConfig::default_prev_candidate")]
+  pub prev_candidate: KeyCombinations,
 }
 
 impl KeyConfig {
+  // ctrl+qとqのように複数の設定を満たす場合は、キー数の多い組み合わせを優先する
   pub fn try_change_transformer(
     &self,
     allow: &HashSet<TransformerTypes>,
     pressing_keys: &HashSet<KeyCode>,
   ) -> Option<TransformerTypes> {
-    if allow.contains(&TransformerTypes::Hiragana)
-      && self.enter_hiragana_transformer.fulfilled(pressing_keys)
-    {
-      return Some(TransformerTypes::Hiragana);
-    }
-    if allow.contains(&TransformerTypes::Katakana)
-      && self.enter_katakana_transformer.fulfilled(pressing_keys)
-    {
-      return Some(TransformerTypes::Katakana);
-    }
-    if allow.contains(&TransformerTypes::EnKatakana)
-      && self.enter_en_katakana_transformer.fulfilled(pressing_keys)
-    {
-      return Some(TransformerTypes::EnKatakana);
-    }
-    if allow.contains(&TransformerTypes::EmEisu)
-      && self.enter_em_eisu_transformer.fulfilled(pressing_keys)
-    {
-      return Some(TransformerTypes::EmEisu);
-    }
-    if allow.contains(&TransformerTypes::Abbr)
-      && self.enter_abbr_transformer.fulfilled(pressing_keys)
-    {
-      return Some(TransformerTypes::Abbr);
-    }
-    if allow.contains(&TransformerTypes::Direct)
-      && self.enter_direct_transformer.fulfilled(pressing_keys)
-    {
-      return Some(TransformerTypes::Direct);
-    }
+    vec![
+      (TransformerTypes::Hiragana, &self.enter_hiragana_transformer),
+      (TransformerTypes::Katakana, &self.enter_katakana_transformer),
+      (
+        TransformerTypes::EnKatakana,
+        &self.enter_en_katakana_transformer,
+      ),
+      (TransformerTypes::EmEisu, &self.enter_em_eisu_transformer),
+      (TransformerTypes::Abbr, &self.enter_abbr_transformer),
+      (TransformerTypes::Direct, &self.enter_direct_transformer),
+    ]
+    .into_iter()
+    .filter(|(transformer_type, _)| allow.contains(transformer_type))
+    .filter_map(|(transformer_type, combinations)| {
+      Some((transformer_type, combinations.fulfilled_len(pressing_keys)?))
+    })
+    .fold(None, |acc, (transformer_type, len)| match acc {
+      Some((_, max)) if max >= len => acc,
+      _ => Some((transformer_type, len)),
+    })
+    .map(|(transformer_type, _)| transformer_type)
+  }
 
-    None
+  // C-gなどの組み合わせを、対応するキーに置き換える
+  // 余計な修飾キーが押されているときは置き換えない
+  pub fn try_action(
+    &self,
+    pressing_keys: &HashSet<KeyCode>,
+    is_selecting_candidate: bool,
+  ) -> Option<KeyCode> {
+    vec![
+      (KeyCode::from("escape"), &self.cancel, true),
+      (KeyCode::from("backspace"), &self.backspace, true),
+      (KeyCode::from("enter"), &self.enter, true),
+      (
+        KeyCode::from("space"),
+        &self.next_candidate,
+        is_selecting_candidate,
+      ),
+      (
+        KeyCode::from("backspace"),
+        &self.prev_candidate,
+        is_selecting_candidate,
+      ),
+    ]
+    .into_iter()
+    .find(|(_, combinations, is_enabled)| *is_enabled && combinations.matches(pressing_keys))
+    .map(|(key, _, _)| key)
   }
 
   pub fn default_config() -> Self {
     KeyConfig {
-      enter: combos![combo![KeyCode::from("enter")]],
-      enter_hiragana_transformer: combos![combo![KeyCode::from("ctrl"), KeyCode::from("j")]],
+      enter: combos![
+        combo![KeyCode::from("enter")],
+        combo![KeyCode::from("ctrl"), KeyCode::from("m")]
+      ],
+      enter_hiragana_transformer: combos![
+        combo![KeyCode::from("ctrl"), KeyCode::from("j")],
+        combo![KeyCode::from("kana")]
+      ],
       enter_katakana_transformer: combos![combo![KeyCode::from("q")]],
       enter_en_katakana_transformer: combos![combo![KeyCode::from("ctrl"), KeyCode::from("q")]],
       enter_em_eisu_transformer: combos![combo![KeyCode::from("shift"), KeyCode::from("l")]],
       enter_abbr_transformer: combos![combo![KeyCode::from("/")]],
-      enter_direct_transformer: combos![combo![KeyCode::from("l")]],
+      enter_direct_transformer: combos![combo![KeyCode::from("l")], combo![KeyCode::from("eisu")]],
       sticky_key: combos![combo![KeyCode::from(";")]],
+      toggle_private_mode: Self::default_toggle_private_mode(),
+      cancel: Self::default_cancel(),
+      backspace: Self::default_backspace(),
+      next_candidate: Self::default_next_candidate(),
+      prev_candidate: Self::default_prev_candidate(),
+    }
+  }
+
+  fn default_cancel() -> KeyCombinations {
+    combos![combo![KeyCode::from("ctrl"), KeyCode::from("g")]]
+  }
+
+  fn default_backspace() -> KeyCombinations {
+    combos![combo![KeyCode::from("ctrl"), KeyCode::from("h")]]
+  }
+
+  fn default_next_candidate() -> KeyCombinations {
+    combos![combo![KeyCode::from("ctrl"), KeyCode::from("n")]]
+  }
+
+  fn default_prev_candidate() -> KeyCombinations {
+    combos![combo![KeyCode::from("ctrl"), KeyCode::from("p")]]
+  }
+
+  // q・l・/などはかなに割り当てられているため、altとの組み合わせでモードを切り替える
+  pub fn default_kana_config() -> Self {
+    KeyConfig {
+      enter_katakana_transformer: combos![combo![KeyCode::from("alt"), KeyCode::from("q")]],
+      enter_em_eisu_transformer: combos![combo![
+        KeyCode::from("alt"),
+        KeyCode::from("shift"),
+        KeyCode::from("l")
+      ]],
+      enter_abbr_transformer: combos![combo![KeyCode::from("alt"), KeyCode::from("/")]],
+      enter_direct_transformer: combos![
+        combo![KeyCode::from("alt"), KeyCode::from("l")],
+        combo![KeyCode::from("eisu")]
+      ],
+      ..Self::default_config()
     }
   }
+
+  fn default_toggle_private_mode() -> KeyCombinations {
+    combos![combo![
+      KeyCode::from("ctrl"),
+      KeyCode::from("shift"),
+      KeyCode::from("p")
+    ]]
+  }
 }
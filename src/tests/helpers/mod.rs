@@ -2,27 +2,38 @@
 
 pub mod transformer;
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::keyboards::{KeyCode, KeyEvents, MetaKey};
-use crate::{Context, Dictionary, RSKKConfig};
+use crate::transformers::TransformerTypes;
+use crate::{Context, Dictionary, RSKKConfig, RSKK};
 use KeyEvents::*;
 
 pub use transformer::*;
 
-pub fn dummy_context() -> Context {
-  Context::new(
-    Rc::new(RSKKConfig::default_config()),
-    Rc::new(Dictionary::parse(
-      "
+const DUMMY_DICTIONARY: &str = "
 かんじ /漢字/
 みち /未知/道/
 ご /語/
 おくr /送/
 test /テスト/
-    ",
-    )),
-  )
+    ";
+
+pub fn dummy_context() -> Context {
+  dummy_context_with(RSKKConfig::default_config())
+}
+
+pub fn dummy_context_with(config: RSKKConfig) -> Context {
+  Context::new(Arc::new(config), Arc::new(Dictionary::parse(DUMMY_DICTIONARY)))
+}
+
+pub fn rskk_with(config: RSKKConfig) -> RSKK {
+  let rskk = RSKK::new(TransformerTypes::Hiragana);
+  rskk
+    .parse_config(&serde_json::to_string(&config).unwrap())
+    .unwrap();
+
+  rskk
 }
 
 pub fn str_to_key_code_vector(string: &str) -> Vec<KeyEvents> {
@@ -42,9 +53,8 @@ pub fn str_to_key_code_vector(string: &str) -> Vec<KeyEvents> {
 }
 
 fn head_token(string: &str) -> (Option<Vec<KeyEvents>>, usize) {
-  let (head, tail) = string.split_at(1);
-  let head = head.chars().next();
-  let head = head.unwrap_or(' ');
+  let head = string.chars().next().unwrap_or(' ');
+  let (_, tail) = string.split_at(head.len_utf8());
   if is_meta_start(head) {
     let end_pos = tail.find(']');
     if end_pos.is_none() {
@@ -56,7 +66,7 @@ fn head_token(string: &str) -> (Option<Vec<KeyEvents>>, usize) {
     return (parse_token(token), end_pos + 2);
   }
 
-  return (parse_token(&head.to_string()), 1);
+  return (parse_token(&head.to_string()), head.len_utf8());
 }
 
 fn is_meta_start(character: char) -> bool {
@@ -76,6 +86,7 @@ fn parse_token(token: &str) -> Option<Vec<KeyEvents>> {
     "up" => Some(vec![KeyEvents::KeyUp(key)]),
     "down" => Some(vec![KeyEvents::KeyDown(key)]),
     "repeat" => Some(vec![KeyEvents::KeyRepeat(key)]),
+    "char" => Some(vec![KeyEvents::Character(key.printable_key()?)]),
     _ => None,
   }
 }
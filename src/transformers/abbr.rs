@@ -32,10 +32,14 @@ impl AbbrTransformer {
   fn try_transition_to_select_candidate(&self) -> Option<SelectCandidateTransformer> {
     self
       .context
-      .dictionary()
-      .transform(self.to_word().to_dic_read()?)
-      .map(|dic_entry| {
-        SelectCandidateTransformer::new(self.clone_context(), dic_entry, self.to_word())
+      .lookup(&self.to_word().to_dic_read()?)
+      .map(|(dic_entry, dynamic_candidates)| {
+        SelectCandidateTransformer::new(
+          self.clone_context(),
+          &dic_entry,
+          &dynamic_candidates,
+          self.to_word(),
+        )
       })
   }
 
@@ -207,9 +211,12 @@ impl Stackable for AbbrTransformer {
 
 #[cfg(test)]
 mod tests {
+  use crate::dictionary::{DateTimeProvider, DynamicCandidateProviders, FixedClock};
   use crate::tests::dummy_context;
   use crate::transformers::StoppedReason::*;
   use crate::transformers::TransformerTypes::*;
+  use crate::Context;
+  use std::sync::Arc;
 
   #[test]
   fn it_works() {
@@ -228,4 +235,28 @@ mod tests {
     ];
     crate::tests::helpers::TestData::batch(vec);
   }
+
+  #[test]
+  fn date_time() {
+    // 2026-10-17 13:05:09 JST
+    let clock = FixedClock(1792209909);
+    let conf = dummy_context();
+    let conf = Context::new_with_providers(
+      Arc::new(conf.config().clone()),
+      Arc::new(conf.dictionary().clone()),
+      Arc::new(DynamicCandidateProviders::new(vec![Arc::new(
+        DateTimeProvider::new(box clock),
+      )])),
+    );
+
+    let vec = crate::tds![conf, Abbr;
+      ["today ", { display: "▼2026-10-17", transformer_type: Abbr }],
+      ["today  ", { display: "▼2026年10月17日", transformer_type: Abbr }],
+      ["today   ", { display: "▼令和8年10月17日", transformer_type: Abbr }],
+      ["today    \n", { stopped_buffer: "2026/10/17(土)", transformer_type: Stopped(Compleated) }],
+      ["now ", { display: "▼13:05", transformer_type: Abbr }],
+      ["now  \n", { stopped_buffer: "13時05分", transformer_type: Stopped(Compleated) }],
+    ];
+    crate::tests::helpers::TestData::batch(vec);
+  }
 }
@@ -61,7 +61,8 @@ impl ContinuousTransformer {
       None => self.clone(),
       Some(vec) => {
         let mut ret = self.clone();
-        ret.push_dictionary_updates(&vec);
+        let context = ret.push_dictionary_updates(&vec);
+        ret.set_context(context);
 
         ret
       }
@@ -0,0 +1,140 @@
+use super::tables::{BufferPairs, LetterType};
+use super::{
+  AsTransformerTrait, Displayable, Stackable, Transformable, TransformerTypes, WithContext,
+};
+use crate::keyboards::{KeyCode, Keyboard};
+use crate::{set, tf, Context};
+use std::collections::HashSet;
+
+#[derive(Clone, Debug)]
+pub struct EmEisuTransformer {
+  context: Context,
+  buffer: BufferPairs,
+}
+
+impl EmEisuTransformer {
+  pub fn new(context: Context) -> Self {
+    EmEisuTransformer {
+      context,
+      buffer: BufferPairs::new(LetterType::EmEisu),
+    }
+  }
+
+  fn allow_transformers() -> HashSet<TransformerTypes> {
+    set![TransformerTypes::Hiragana]
+  }
+}
+
+impl WithContext for EmEisuTransformer {
+  fn clone_context(&self) -> Context {
+    self.context.clone()
+  }
+
+  fn context(&self) -> &Context {
+    &self.context
+  }
+
+  fn set_context(&mut self, context: Context) {
+    self.context = context;
+  }
+}
+
+impl Transformable for EmEisuTransformer {
+  fn transformer_type(&self) -> TransformerTypes {
+    TransformerTypes::EmEisu
+  }
+
+  fn try_change_transformer(
+    &self,
+    keyboard: &Box<dyn Keyboard>,
+    _: &KeyCode,
+  ) -> Option<Box<dyn Transformable>> {
+    let transformer_type = self
+      .context
+      .config()
+      .key_config()
+      .try_change_transformer(&Self::allow_transformers(), keyboard.pressing_keys());
+
+    Some(tf!(self.clone_context(), transformer_type?))
+  }
+
+  fn push_character(&self, character: char) -> Option<Vec<Box<dyn Transformable>>> {
+    let mut tf = self.clone();
+
+    tf.buffer.push(character);
+    let (stopped, continued) = tf.buffer.partition_by_state();
+    tf.buffer = continued;
+    Some(vec![match tf.is_empty() {
+      true => tf.to_completed_with_update_buffer(stopped.to_string()),
+      false => {
+        tf.push_stopped_buffer(stopped.to_string());
+        box tf
+      }
+    }])
+  }
+
+  fn push_space(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    self.push_character(' ')
+  }
+}
+
+impl Stackable for EmEisuTransformer {
+  fn push(&self, _: Box<dyn Transformable>) -> Box<dyn Transformable> {
+    unreachable!()
+  }
+
+  fn pop(&self) -> (Box<dyn Transformable>, Option<Box<dyn Transformable>>) {
+    unreachable!()
+  }
+
+  fn replace_last_element(&self, _: Vec<Box<dyn Transformable>>) -> Vec<Box<dyn Transformable>> {
+    vec![box self.clone()]
+  }
+
+  fn stack(&self) -> Vec<Box<dyn Transformable>> {
+    vec![]
+  }
+
+  fn child_transformer_type(&self) -> TransformerTypes {
+    TransformerTypes::EmEisu
+  }
+}
+
+impl Displayable for EmEisuTransformer {
+  fn buffer_content(&self) -> String {
+    self.buffer.to_string()
+  }
+
+  fn display_string(&self) -> String {
+    self.buffer_content()
+  }
+}
+
+impl AsTransformerTrait for EmEisuTransformer {
+  fn as_trait(&self) -> Box<dyn Transformable> {
+    Box::new(self.clone())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::tests::dummy_context;
+  use crate::transformers::StoppedReason::*;
+  use crate::transformers::TransformerTypes::*;
+
+  #[test]
+  fn it_works() {
+    let conf = dummy_context();
+
+    let vec = crate::tds!(conf, EmEisu;
+      ["[escape]", { display: "", transformer_type: EmEisu }],
+      ["a", { display: "", stopped_buffer: "ａ", transformer_type: Stopped(Compleated) }],
+      ["A", { display: "", stopped_buffer: "Ａ", transformer_type: Stopped(Compleated) }],
+      ["!", { display: "", stopped_buffer: "！", transformer_type: Stopped(Compleated) }],
+      ["l", { display: "", stopped_buffer: "ｌ", transformer_type: Stopped(Compleated) }],
+      [" ", { display: "", stopped_buffer: "\u{3000}", transformer_type: Stopped(Compleated) }],
+      ["[down:ctrl]j", { display: "", transformer_type: Hiragana }],
+    );
+    crate::tests::helpers::TestData::batch(vec);
+  }
+}
@@ -0,0 +1,266 @@
+use super::tables::{BufferPairs, LetterType};
+use super::{
+  AbbrTransformer, AsTransformerTrait, Displayable, HenkanTransformer, Stackable, Transformable,
+  TransformerTypes, WithContext,
+};
+use crate::keyboards::{KeyCode, Keyboard};
+use crate::{set, tf, Context};
+use std::collections::HashSet;
+
+#[derive(Clone)]
+pub struct EnKatakanaTransformer {
+  context: Context,
+  buffer: BufferPairs,
+}
+
+impl EnKatakanaTransformer {
+  pub fn new(context: Context) -> Self {
+    let rule_table = context.rule_table();
+
+    EnKatakanaTransformer {
+      context,
+      buffer: BufferPairs::new_with_rules(LetterType::EnKatakana, rule_table),
+    }
+  }
+
+  fn allow_transformers() -> HashSet<TransformerTypes> {
+    set![
+      TransformerTypes::Direct,
+      TransformerTypes::Hiragana,
+      TransformerTypes::Katakana,
+      TransformerTypes::EmEisu,
+      TransformerTypes::Abbr
+    ]
+  }
+
+  fn try_enter_henkan(&self, character: char) -> Option<Box<dyn Transformable>> {
+    // かな入力では、小書きのかななどに割り当てられているシフトの文字は変換を始めない
+    match character.is_uppercase() && !self.buffer.rules().is_mapped(character) {
+      true => Some(box HenkanTransformer::new(
+        self.new_context(),
+        TransformerTypes::EnKatakana,
+      )),
+      false => None,
+    }
+  }
+
+  fn try_enter_abbr(&self, character: char) -> Option<Box<dyn Transformable>> {
+    match character {
+      // z/のように入力途中の列に続く場合はルールを優先する
+      '/' if self.buffer.continues(character) => None,
+      '/' if self.buffer.rules().is_kana_input() => None,
+      '/' => Some(box AbbrTransformer::new(self.new_context())),
+      _ => None,
+    }
+  }
+}
+
+impl WithContext for EnKatakanaTransformer {
+  fn clone_context(&self) -> Context {
+    self.context.clone()
+  }
+
+  fn context(&self) -> &Context {
+    &self.context
+  }
+
+  fn set_context(&mut self, context: Context) {
+    self.context = context;
+  }
+}
+
+impl Transformable for EnKatakanaTransformer {
+  fn transformer_type(&self) -> TransformerTypes {
+    TransformerTypes::EnKatakana
+  }
+
+  fn try_change_transformer(
+    &self,
+    keyboard: &Box<dyn Keyboard>,
+    key: &KeyCode,
+  ) -> Option<Box<dyn Transformable>> {
+    // ローマ字のルールで使われているキーは、キー設定よりも優先する
+    if !keyboard.is_combination() && key.printable_key().map_or(false, |c| self.buffer.claims(c)) {
+      return None;
+    }
+
+    let config = self.context.config();
+    // 半角カタカナへの切り替えキーは、qより優先してひらがなに戻す
+    if config
+      .key_config()
+      .enter_en_katakana_transformer
+      .fulfilled(keyboard.pressing_keys())
+    {
+      return Some(tf!(self.clone_context(), TransformerTypes::Hiragana));
+    }
+
+    let transformer_type = config
+      .key_config()
+      .try_change_transformer(&Self::allow_transformers(), keyboard.pressing_keys())
+      .or_else(|| {
+        match config
+          .romaji_table
+          .toggle_kana_keys()
+          .fulfilled(keyboard.pressing_keys())
+        {
+          true => Some(TransformerTypes::Hiragana),
+          false => None,
+        }
+      });
+
+    Some(tf!(self.clone_context(), transformer_type?))
+  }
+
+  fn push_character(&self, character: char) -> Option<Vec<Box<dyn Transformable>>> {
+    if let Some(tf) = self.try_enter_abbr(character) {
+      return Some(vec![tf]);
+    }
+    if let Some(tf) = self.try_enter_henkan(character) {
+      return tf.push_character(character.to_lowercase().next()?);
+    }
+
+    let mut tf = self.clone();
+    tf.buffer.push(character);
+    let (stopped, continued) = tf.buffer.partition_by_state();
+    tf.buffer = continued;
+    tf.set_context(tf.clear_stopped_buffer());
+    Some(vec![match tf.is_empty() {
+      true => tf.to_completed_with_update_buffer(stopped.to_string()),
+      false => {
+        tf.set_context(tf.push_stopped_buffer(stopped.to_string()));
+        box tf
+      }
+    }])
+  }
+
+  fn push_enter(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    if self.buffer.is_empty() {
+      return None;
+    }
+
+    let mut tf = self.clone();
+    if tf.context.config().is_flush_pending_input() {
+      tf.buffer.flush();
+    }
+    let (stopped, _) = tf.buffer.partition_by_state();
+    tf.set_context(tf.clear_stopped_buffer());
+
+    Some(vec![tf.to_completed_with_update_buffer(stopped.to_string())])
+  }
+
+  fn to_completed_with_literal(&self, character: char) -> Box<dyn Transformable> {
+    // 入力途中のローマ字は確定し、かなにならない分もそのまま残す
+    let mut tf = self.clone();
+    tf.buffer.flush();
+    tf.set_context(tf.clear_stopped_buffer());
+
+    tf.to_completed_with_update_buffer(tf.buffer.to_string() + &character.to_string())
+  }
+
+  fn push_escape(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    match self.buffer.is_empty() {
+      true => None,
+      false => Some(vec![]),
+    }
+  }
+
+  fn push_backspace(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    match self.buffer.is_empty() {
+      true => None,
+      false => {
+        let mut tf = self.clone();
+        tf.buffer.remove_last();
+
+        match tf.buffer.is_empty() {
+          true => Some(vec![]),
+          false if tf.buffer.is_stopped() => Some(vec![tf.to_completed()]),
+          false => Some(vec![box tf]),
+        }
+      }
+    }
+  }
+
+  fn push_delete(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    self.push_backspace()
+  }
+}
+
+impl Displayable for EnKatakanaTransformer {
+  fn buffer_content(&self) -> String {
+    self.buffer.to_string()
+  }
+
+  fn display_string(&self) -> String {
+    self.buffer_content()
+  }
+}
+
+impl AsTransformerTrait for EnKatakanaTransformer {
+  fn as_trait(&self) -> Box<dyn Transformable> {
+    box self.clone()
+  }
+}
+
+impl Stackable for EnKatakanaTransformer {
+  fn push(&self, _: Box<dyn Transformable>) -> Box<dyn Transformable> {
+    unreachable!()
+  }
+
+  fn pop(&self) -> (Box<dyn Transformable>, Option<Box<dyn Transformable>>) {
+    let mut tf = self.clone();
+    let tf = match tf.buffer.is_empty() {
+      true => tf.to_canceled(),
+      false => {
+        tf.buffer.remove_last();
+        box tf
+      }
+    };
+
+    (tf, None)
+  }
+
+  fn replace_last_element(&self, _: Vec<Box<dyn Transformable>>) -> Vec<Box<dyn Transformable>> {
+    vec![box self.clone()]
+  }
+
+  fn stack(&self) -> Vec<Box<dyn Transformable>> {
+    vec![]
+  }
+
+  fn child_transformer_type(&self) -> TransformerTypes {
+    TransformerTypes::EnKatakana
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::tests::dummy_context;
+  use crate::transformers::StoppedReason::*;
+  use crate::transformers::TransformerTypes::*;
+
+  #[test]
+  fn it_works() {
+    let conf = dummy_context();
+
+    let vec = crate::tds![conf, EnKatakana;
+      ["a", { display: "", stopped_buffer: "ｱ", transformer_type: Stopped(Compleated) }],
+      ["k", { display: "k", transformer_type: EnKatakana }],
+      ["k[escape]", { display: "", transformer_type: Stopped(Canceled) }],
+      ["k[backspace]", { display: "", transformer_type: Stopped(Canceled) }],
+      ["ts[backspace]", { display: "t", transformer_type: EnKatakana }],
+      ["ka", { display: "", stopped_buffer: "ｶ", transformer_type: Stopped(Compleated) }],
+      ["ga", { display: "", stopped_buffer: "ｶﾞ", transformer_type: Stopped(Compleated) }],
+      ["tt", { display: "t", stopped_buffer: "ｯ", transformer_type: EnKatakana }],
+      ["tt[backspace]", { display: "", stopped_buffer: "ｯ", transformer_type: Stopped(Canceled) }],
+      ["tte", { display: "", stopped_buffer: "ﾃ", transformer_type: Stopped(Compleated) }],
+      ["kyo", { display: "", stopped_buffer: "ｷｮ", transformer_type: Stopped(Compleated) }],
+      ["-", { display: "", stopped_buffer: "ｰ", transformer_type: Stopped(Compleated) }],
+      ["Kannji", { display: "▽ｶﾝｼﾞ", transformer_type: Henkan }],
+      ["Kanji", { display: "▽ｶﾝｼﾞ", transformer_type: Henkan }],
+      ["q", { display: "", transformer_type: Katakana }],
+      ["[down:ctrl]q", { display: "", transformer_type: Hiragana }],
+      ["[down:ctrl]q[up:ctrl]ka", { stopped_buffer: "か", transformer_type: Stopped(Compleated) }],
+    ];
+    crate::tests::helpers::TestData::batch(vec);
+  }
+}
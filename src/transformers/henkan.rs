@@ -2,6 +2,7 @@ use super::{
   AsTransformerTrait, Displayable, KeyCode, Stackable, Transformable, TransformerTypes,
   WithContext, YomiTransformer,
 };
+use crate::keyboards::Keyboard;
 use crate::{tf, Context};
 
 #[derive(Clone)]
@@ -43,6 +44,18 @@ impl Transformable for HenkanTransformer {
     TransformerTypes::Henkan
   }
 
+  fn try_change_transformer(
+    &self,
+    keyboard: &Box<dyn Keyboard>,
+    key: &KeyCode,
+  ) -> Option<Box<dyn Transformable>> {
+    let tf = self.stack.last()?.try_change_transformer(keyboard, key)?;
+    match tf.is_stopped() {
+      true => Some(tf),
+      false => self.replace_last_element(vec![tf]).last().cloned(),
+    }
+  }
+
   fn push_character(&self, character: char) -> Option<Vec<Box<dyn Transformable>>> {
     Some(self.replace_last_element(self.stack.last()?.push_character(character)?))
   }
@@ -72,6 +85,22 @@ impl Transformable for HenkanTransformer {
     Some(self.replace_last_element(self.stack.last()?.push_backspace()?))
   }
 
+  fn push_arrow_left(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    Some(self.replace_last_element(self.stack.last()?.push_arrow_left()?))
+  }
+
+  fn push_arrow_right(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    Some(self.replace_last_element(self.stack.last()?.push_arrow_right()?))
+  }
+
+  fn push_home(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    Some(self.replace_last_element(self.stack.last()?.push_home()?))
+  }
+
+  fn push_end(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    Some(self.replace_last_element(self.stack.last()?.push_end()?))
+  }
+
   fn push_any_character(&self, key_code: &KeyCode) -> Option<Vec<Box<dyn Transformable>>> {
     let tfs = self.stack.last()?.push_any_character(key_code)?;
 
@@ -114,6 +143,10 @@ impl Displayable for HenkanTransformer {
       .and_then(|tf| Some(tf.display_string()))
       .unwrap_or(String::new())
   }
+
+  fn cursor_offset(&self) -> usize {
+    self.stack.last().map(|tf| tf.cursor_offset()).unwrap_or(0)
+  }
 }
 
 impl AsTransformerTrait for HenkanTransformer {
@@ -185,6 +218,7 @@ mod tests {
       ["hiragana[escape]", { display: "", transformer_type: Stopped(Canceled) }],
       ["kannji ", { display: "▼漢字", transformer_type: Henkan }],
       ["kannji [backspace]", { display: "▽かんじ", transformer_type: Henkan }],
+      ["anji[home]ka", { display: "▽かあんじ", transformer_type: Henkan }],
       ["kannji \n", { stopped_buffer: "漢字", transformer_type: Stopped(Compleated) }],
       ["okuR", { display: "▽おく*r", transformer_type: Henkan }],
       ["okuR\n", { stopped_buffer: "おく", transformer_type: Stopped(Compleated) }],
@@ -222,9 +256,32 @@ mod tests {
       ["Michigo  [escape]", { display: "▽みちご", transformer_type: Henkan }],
       ["Michigo  [escape][escape]", { display: "", transformer_type: Stopped(Canceled) }],
       ["Michigo  [escape]a", { display: "▽みちごあ", transformer_type: Henkan }],
+      ["kannji[down:ctrl]q", { stopped_buffer: "ｶﾝｼﾞ", display: "", transformer_type: Stopped(Compleated) }],
     ];
     crate::tests::helpers::TestData::batch(vec);
 
     // TODO: カタカナ時のテスト
   }
+  #[test]
+  fn control_keys() {
+    let conf = dummy_context();
+
+    let vec = crate::tds![conf, HenkanTransformer, Hiragana;
+      ["michi [down:ctrl]n", { display: "▼道", transformer_type: Henkan }],
+      ["michi [down:ctrl]np", { display: "▼未知", transformer_type: Henkan }],
+      ["michi [down:ctrl]m", { stopped_buffer: "未知", transformer_type: Stopped(Compleated) }],
+      ["michi[down:ctrl]h", { display: "▽み", transformer_type: Henkan }],
+      ["michi [down:ctrl]g[up:ctrl]i", { display: "▽みちい", transformer_type: Henkan }],
+      ["michi [down:ctrl]g", { display: "▽みち", transformer_type: Henkan }],
+      // 候補の選択中以外では、C-nやC-pを使わない
+      ["michi[down:ctrl]n", { display: "▽みち", transformer_type: Henkan }],
+      ["michi[down:ctrl]p", { display: "▽みち", transformer_type: Henkan }],
+      // 設定にない修飾キーも押されているときは使わない
+      ["michi[down:ctrl][down:alt]h", { display: "▽みち", transformer_type: Henkan }],
+      ["michi [down:ctrl][down:shift]n", { display: "▼未知", transformer_type: Henkan }],
+      ["michi[down:ctrl]gg", { display: "", transformer_type: Stopped(Canceled) }],
+      ["michi[down:ctrl]m", { stopped_buffer: "みち", transformer_type: Stopped(Compleated) }],
+    ];
+    crate::tests::helpers::TestData::batch(vec);
+  }
 }
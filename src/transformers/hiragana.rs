@@ -16,9 +16,11 @@ pub struct HiraganaTransformer {
 
 impl HiraganaTransformer {
   pub fn new(context: Context) -> Self {
+    let rule_table = context.rule_table();
+
     HiraganaTransformer {
       context,
-      buffer: BufferPairs::new(LetterType::Hiragana),
+      buffer: BufferPairs::new_with_rules(LetterType::Hiragana, rule_table),
     }
   }
 
@@ -27,12 +29,14 @@ impl HiraganaTransformer {
       TransformerTypes::Direct,
       TransformerTypes::Katakana,
       TransformerTypes::EnKatakana,
-      TransformerTypes::EmEisu
+      TransformerTypes::EmEisu,
+      TransformerTypes::Abbr
     ]
   }
 
   fn try_enter_henkan(&self, character: char) -> Option<Box<dyn Transformable>> {
-    match character.is_uppercase() {
+    // かな入力では、小書きのかななどに割り当てられているシフトの文字は変換を始めない
+    match character.is_uppercase() && !self.buffer.rules().is_mapped(character) {
       true => Some(box HenkanTransformer::new(
         self.new_context(),
         TransformerTypes::Hiragana,
@@ -43,6 +47,9 @@ impl HiraganaTransformer {
 
   fn try_enter_abbr(&self, character: char) -> Option<Box<dyn Transformable>> {
     match character {
+      // z/のように入力途中の列に続く場合はルールを優先する
+      '/' if self.buffer.continues(character) => None,
+      '/' if self.buffer.rules().is_kana_input() => None,
       '/' => Some(box AbbrTransformer::new(self.new_context())),
       _ => None,
     }
@@ -71,13 +78,27 @@ impl Transformable for HiraganaTransformer {
   fn try_change_transformer(
     &self,
     keyboard: &Box<dyn Keyboard>,
-    _: &KeyCode,
+    key: &KeyCode,
   ) -> Option<Box<dyn Transformable>> {
-    let transformer_type = self
-      .context
-      .config()
+    // ローマ字のルールで使われているキーは、キー設定よりも優先する
+    if !keyboard.is_combination() && key.printable_key().map_or(false, |c| self.buffer.claims(c)) {
+      return None;
+    }
+
+    let config = self.context.config();
+    let transformer_type = config
       .key_config()
-      .try_change_transformer(&Self::allow_transformers(), keyboard.pressing_keys());
+      .try_change_transformer(&Self::allow_transformers(), keyboard.pressing_keys())
+      .or_else(|| {
+        match config
+          .romaji_table
+          .toggle_kana_keys()
+          .fulfilled(keyboard.pressing_keys())
+        {
+          true => Some(TransformerTypes::Katakana),
+          false => None,
+        }
+      });
 
     Some(tf!(self.clone_context(), transformer_type?))
   }
@@ -104,6 +125,30 @@ impl Transformable for HiraganaTransformer {
     }])
   }
 
+  fn push_enter(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    if self.buffer.is_empty() {
+      return None;
+    }
+
+    let mut tf = self.clone();
+    if tf.context.config().is_flush_pending_input() {
+      tf.buffer.flush();
+    }
+    let (stopped, _) = tf.buffer.partition_by_state();
+    tf.set_context(tf.clear_stopped_buffer());
+
+    Some(vec![tf.to_completed_with_update_buffer(stopped.to_string())])
+  }
+
+  fn to_completed_with_literal(&self, character: char) -> Box<dyn Transformable> {
+    // 入力途中のローマ字は確定し、かなにならない分もそのまま残す
+    let mut tf = self.clone();
+    tf.buffer.flush();
+    tf.set_context(tf.clear_stopped_buffer());
+
+    tf.to_completed_with_update_buffer(tf.buffer.to_string() + &character.to_string())
+  }
+
   fn push_escape(&self) -> Option<Vec<Box<dyn Transformable>>> {
     match self.buffer.is_empty() {
       true => None,
@@ -181,7 +226,7 @@ impl Stackable for HiraganaTransformer {
 
 #[cfg(test)]
 mod tests {
-  use crate::tests::dummy_context;
+  use crate::tests::{dummy_context, dummy_context_with};
   use crate::transformers::StoppedReason::*;
   use crate::transformers::TransformerTypes::*;
 
@@ -201,6 +246,160 @@ mod tests {
       ["tte", { display: "", stopped_buffer: "て", transformer_type: Stopped(Compleated) }],
       ["Kannji", { display: "▽かんじ", transformer_type: Henkan }],
       ["Kanji", { display: "▽かんじ", transformer_type: Henkan }],
+      ["q", { display: "", transformer_type: Katakana }],
+      ["[down:ctrl]q", { display: "", transformer_type: EnKatakana }],
+      ["[down:ctrl]q[up:ctrl]ka", { stopped_buffer: "ｶ", transformer_type: Stopped(Compleated) }],
+      ["l", { display: "", transformer_type: Direct }],
+      ["L", { display: "", transformer_type: EmEisu }],
+      ["La", { stopped_buffer: "ａ", transformer_type: Stopped(Compleated) }],
+      ["/", { display: "▽", transformer_type: Abbr }],
+      ["z", { display: "z", transformer_type: Hiragana }],
+      ["z/", { stopped_buffer: "・", transformer_type: Stopped(Compleated) }],
+      ["z.", { stopped_buffer: "…", transformer_type: Stopped(Compleated) }],
+      ["z,", { stopped_buffer: "‥", transformer_type: Stopped(Compleated) }],
+      ["zh", { stopped_buffer: "←", transformer_type: Stopped(Compleated) }],
+      ["zj", { stopped_buffer: "↓", transformer_type: Stopped(Compleated) }],
+      ["zk", { stopped_buffer: "↑", transformer_type: Stopped(Compleated) }],
+      ["zl", { stopped_buffer: "→", transformer_type: Stopped(Compleated) }],
+      ["z-", { stopped_buffer: "〜", transformer_type: Stopped(Compleated) }],
+      ["z[[]", { stopped_buffer: "『", transformer_type: Stopped(Compleated) }],
+      ["za", { stopped_buffer: "ざ", transformer_type: Stopped(Compleated) }],
+      ["zya", { stopped_buffer: "じゃ", transformer_type: Stopped(Compleated) }],
+      ["z[backspace]", { display: "", transformer_type: Stopped(Canceled) }],
+      ["n", { display: "n", transformer_type: Hiragana }],
+      ["n\n", { display: "", stopped_buffer: "ん", transformer_type: Stopped(Compleated) }],
+      ["n'", { display: "", stopped_buffer: "ん", transformer_type: Stopped(Compleated) }],
+      ["nn", { display: "", stopped_buffer: "ん", transformer_type: Stopped(Compleated) }],
+      ["nk", { display: "k", stopped_buffer: "ん", transformer_type: Hiragana }],
+      ["nka", { display: "", stopped_buffer: "か", transformer_type: Stopped(Compleated) }],
+      ["n,", { display: "", stopped_buffer: "ん、", transformer_type: Stopped(Compleated) }],
+      ["k\n", { display: "", transformer_type: Stopped(Compleated) }],
+      ["ff", { display: "f", stopped_buffer: "っ", transformer_type: Hiragana }],
+      ["ffa", { display: "", stopped_buffer: "ふぁ", transformer_type: Stopped(Compleated) }],
+    ];
+    crate::tests::helpers::TestData::batch(vec);
+  }
+
+  #[test]
+  fn n_and_sokuon() {
+    let conf = dummy_context();
+
+    let vec = crate::tds![conf, Hiragana;
+      ["n", { display: "n", transformer_type: Hiragana }],
+      ["ni", { stopped_buffer: "に", transformer_type: Stopped(Compleated) }],
+      ["n'", { stopped_buffer: "ん", transformer_type: Stopped(Compleated) }],
+      ["nn", { stopped_buffer: "ん", transformer_type: Stopped(Compleated) }],
+      ["nk", { display: "k", stopped_buffer: "ん", transformer_type: Hiragana }],
+      ["nka", { stopped_buffer: "か", transformer_type: Stopped(Compleated) }],
+      ["ny", { display: "ny", transformer_type: Hiragana }],
+      ["nya", { stopped_buffer: "にゃ", transformer_type: Stopped(Compleated) }],
+      ["n,", { stopped_buffer: "ん、", transformer_type: Stopped(Compleated) }],
+      ["n\n", { stopped_buffer: "ん", transformer_type: Stopped(Compleated) }],
+    ];
+    crate::tests::helpers::TestData::batch(vec);
+
+    let tf = crate::tf!(conf, Hiragana);
+    let rules = conf.rule_table();
+    // 確定した分はキーごとに出力されるので、促音と続くかなを分けて確かめる
+    let vec = "wrtypsdghjkzxcvbmf"
+      .chars()
+      .flat_map(|c| "aiueo".chars().map(move |v| (c, v)))
+      .filter_map(|(c, v)| Some((c, v, rules.get(&format!("{}{}", c, v))?.output.clone())))
+      .flat_map(|(c, v, output)| {
+        vec![
+          crate::tests::helpers::TestData::new(
+            format!("{}{}", c, c),
+            tf.clone(),
+            crate::td!({ display: c.to_string(), stopped_buffer: "っ", transformer_type: Hiragana }),
+          ),
+          crate::tests::helpers::TestData::new(
+            format!("{}{}{}", c, c, v),
+            tf.clone(),
+            crate::td!({ stopped_buffer: output, transformer_type: Stopped(Compleated) }),
+          ),
+        ]
+      })
+      .collect::<Vec<_>>();
+    assert!(vec.len() > 18 * 4 * 2);
+    crate::tests::helpers::TestData::batch(vec);
+  }
+
+  #[test]
+  fn key_repeat() {
+    let conf = dummy_context();
+
+    let vec = crate::tds![conf, Hiragana;
+      ["K[down:a][repeat:a][repeat:a]", { display: "▽かああ", transformer_type: Henkan }],
+      ["Kanji[down:backspace][repeat:backspace]", { display: "▽か", transformer_type: Henkan }],
+      ["[down:l][repeat:l]", { stopped_buffer: "l", transformer_type: Stopped(Compleated) }],
+    ];
+    crate::tests::helpers::TestData::batch(vec);
+  }
+
+  #[test]
+  fn character() {
+    let conf = dummy_context();
+
+    let vec = crate::tds![conf, Hiragana;
+      ["[char:a]", { stopped_buffer: "あ", transformer_type: Stopped(Compleated) }],
+      ["[char:é]", { stopped_buffer: "é", transformer_type: Stopped(Compleated) }],
+      ["[char:😀]", { stopped_buffer: "😀", transformer_type: Stopped(Compleated) }],
+      ["K[char:a]", { display: "▽か", transformer_type: Henkan }],
+      ["K[char:a][char:😀]", { display: "▽か😀", transformer_type: Henkan }],
+    ];
+    crate::tests::helpers::TestData::batch(vec);
+  }
+
+  #[test]
+  fn sticky_shift() {
+    let mut config = crate::RSKKConfig::default_config();
+    config.is_enable_sticky_shift = true;
+    let conf = dummy_context_with(config);
+
+    let vec = crate::tds![conf, Hiragana;
+      [";", { display: "", transformer_type: Hiragana }],
+      [";k", { display: "▽k", transformer_type: Henkan }],
+      [";kanji", { display: "▽かんじ", transformer_type: Henkan }],
+      [";kannji ", { display: "▼漢字", transformer_type: Henkan }],
+      [";oku;ri", { display: "▼送り", transformer_type: Henkan }],
+      [";;", { stopped_buffer: ";", transformer_type: Stopped(Compleated) }],
+      ["k;;", { stopped_buffer: "k;", transformer_type: Stopped(Compleated) }],
+      ["n;;", { stopped_buffer: "ん;", transformer_type: Stopped(Compleated) }],
+      [";[escape]a", { stopped_buffer: "あ", transformer_type: Stopped(Compleated) }],
+      [";[down:shift];", { stopped_buffer: ":", transformer_type: Stopped(Compleated) }],
+    ];
+    crate::tests::helpers::TestData::batch(vec);
+  }
+
+  #[test]
+  fn kana_input() {
+    let mut config = crate::RSKKConfig::default_config();
+    config.input_method = crate::transformers::InputMethod::Kana;
+    let conf = dummy_context_with(config);
+
+    let vec = crate::tds![conf, Hiragana;
+      ["3", { stopped_buffer: "あ", transformer_type: Stopped(Compleated) }],
+      ["t", { display: "か", transformer_type: Hiragana }],
+      ["t[[]", { stopped_buffer: "が", transformer_type: Stopped(Compleated) }],
+      ["f]", { stopped_buffer: "ぱ", transformer_type: Stopped(Compleated) }],
+      ["tq", { display: "た", stopped_buffer: "か", transformer_type: Hiragana }],
+      ["t\n", { stopped_buffer: "か", transformer_type: Stopped(Compleated) }],
+      ["q", { display: "た", transformer_type: Hiragana }],
+      ["E", { stopped_buffer: "ぃ", transformer_type: Stopped(Compleated) }],
+      ["Z", { stopped_buffer: "っ", transformer_type: Stopped(Compleated) }],
+      ["#", { stopped_buffer: "ぁ", transformer_type: Stopped(Compleated) }],
+      ["/", { stopped_buffer: "め", transformer_type: Stopped(Compleated) }],
+      ["Q", { display: "▽た", transformer_type: Henkan }],
+      ["L", { display: "▽り", transformer_type: Henkan }],
+      ["[down:ctrl]q", { display: "", transformer_type: EnKatakana }],
+      ["l", { stopped_buffer: "り", transformer_type: Stopped(Compleated) }],
+      ["[down:alt]l", { display: "", transformer_type: Direct }],
+      ["[down:alt]l[up:alt]l", { stopped_buffer: "l", transformer_type: Stopped(Compleated) }],
+      ["[down:alt]q", { display: "", transformer_type: Katakana }],
+      ["[down:alt]q[up:alt]3", { stopped_buffer: "ア", transformer_type: Stopped(Compleated) }],
+      ["[down:alt]L", { display: "", transformer_type: EmEisu }],
+      ["[down:alt]/", { display: "▽", transformer_type: Abbr }],
+      ["[eisu]", { display: "", transformer_type: Direct }],
     ];
     crate::tests::helpers::TestData::batch(vec);
   }
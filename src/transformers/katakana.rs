@@ -15,9 +15,11 @@ pub struct KatakanaTransformer {
 
 impl KatakanaTransformer {
   pub fn new(context: Context) -> Self {
+    let rule_table = context.rule_table();
+
     KatakanaTransformer {
       context,
-      buffer: BufferPairs::new(LetterType::Katakana),
+      buffer: BufferPairs::new_with_rules(LetterType::Katakana, rule_table),
     }
   }
 
@@ -26,12 +28,14 @@ impl KatakanaTransformer {
       TransformerTypes::Direct,
       TransformerTypes::Hiragana,
       TransformerTypes::EnKatakana,
-      TransformerTypes::EmEisu
+      TransformerTypes::EmEisu,
+      TransformerTypes::Abbr
     ]
   }
 
   fn try_enter_henkan(&self, character: char) -> Option<Box<dyn Transformable>> {
-    match character.is_uppercase() {
+    // かな入力では、小書きのかななどに割り当てられているシフトの文字は変換を始めない
+    match character.is_uppercase() && !self.buffer.rules().is_mapped(character) {
       true => Some(box HenkanTransformer::new(
         self.new_context(),
         TransformerTypes::Katakana,
@@ -42,6 +46,9 @@ impl KatakanaTransformer {
 
   fn try_enter_abbr(&self, character: char) -> Option<Box<dyn Transformable>> {
     match character {
+      // z/のように入力途中の列に続く場合はルールを優先する
+      '/' if self.buffer.continues(character) => None,
+      '/' if self.buffer.rules().is_kana_input() => None,
       '/' => Some(box AbbrTransformer::new(self.new_context())),
       _ => None,
     }
@@ -70,13 +77,27 @@ impl Transformable for KatakanaTransformer {
   fn try_change_transformer(
     &self,
     keyboard: &Box<dyn Keyboard>,
-    _: &KeyCode,
+    key: &KeyCode,
   ) -> Option<Box<dyn Transformable>> {
-    let transformer_type = self
-      .context
-      .config()
+    // ローマ字のルールで使われているキーは、キー設定よりも優先する
+    if !keyboard.is_combination() && key.printable_key().map_or(false, |c| self.buffer.claims(c)) {
+      return None;
+    }
+
+    let config = self.context.config();
+    let transformer_type = config
       .key_config()
-      .try_change_transformer(&Self::allow_transformers(), keyboard.pressing_keys());
+      .try_change_transformer(&Self::allow_transformers(), keyboard.pressing_keys())
+      .or_else(|| {
+        match config
+          .romaji_table
+          .toggle_kana_keys()
+          .fulfilled(keyboard.pressing_keys())
+        {
+          true => Some(TransformerTypes::Hiragana),
+          false => None,
+        }
+      });
 
     Some(tf!(self.clone_context(), transformer_type?))
   }
@@ -103,6 +124,30 @@ impl Transformable for KatakanaTransformer {
     }])
   }
 
+  fn push_enter(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    if self.buffer.is_empty() {
+      return None;
+    }
+
+    let mut tf = self.clone();
+    if tf.context.config().is_flush_pending_input() {
+      tf.buffer.flush();
+    }
+    let (stopped, _) = tf.buffer.partition_by_state();
+    tf.set_context(tf.clear_stopped_buffer());
+
+    Some(vec![tf.to_completed_with_update_buffer(stopped.to_string())])
+  }
+
+  fn to_completed_with_literal(&self, character: char) -> Box<dyn Transformable> {
+    // 入力途中のローマ字は確定し、かなにならない分もそのまま残す
+    let mut tf = self.clone();
+    tf.buffer.flush();
+    tf.set_context(tf.clear_stopped_buffer());
+
+    tf.to_completed_with_update_buffer(tf.buffer.to_string() + &character.to_string())
+  }
+
   fn push_escape(&self) -> Option<Vec<Box<dyn Transformable>>> {
     match self.buffer.is_empty() {
       true => None,
@@ -200,6 +245,9 @@ mod tests {
       ["tte", { display: "", stopped_buffer: "テ", transformer_type: Stopped(Compleated) }],
       ["Kannji", { display: "▽カンジ", transformer_type: Henkan }],
       ["Kanji", { display: "▽カンジ", transformer_type: Henkan }],
+      ["z/", { stopped_buffer: "・", transformer_type: Stopped(Compleated) }],
+      ["zl", { stopped_buffer: "→", transformer_type: Stopped(Compleated) }],
+      ["za", { stopped_buffer: "ザ", transformer_type: Stopped(Compleated) }],
     ];
     crate::tests::helpers::TestData::batch(vec);
   }
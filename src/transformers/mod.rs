@@ -1,6 +1,8 @@
 mod abbr;
 mod continuous;
 mod direct;
+mod em_eisu;
+mod en_katakana;
 mod henkan;
 mod hiragana;
 mod katakana;
@@ -21,13 +23,18 @@ use crate::{Context, DictionaryEntry};
 pub use abbr::AbbrTransformer;
 pub use continuous::ContinuousTransformer;
 pub use direct::DirectTransformer;
+pub use em_eisu::EmEisuTransformer;
+pub use en_katakana::EnKatakanaTransformer;
 pub use henkan::HenkanTransformer;
 pub use hiragana::HiraganaTransformer;
 pub use katakana::KatakanaTransformer;
 pub use select_candidate::SelectCandidateTransformer;
 pub use stackable::Stackable;
 pub use stopped::{StoppedReason, StoppedTransformer};
-pub use tables::LetterType;
+pub use tables::{
+  is_nicola_key, nicola_lookup, InputMethod, LetterType, PunctuationStyle, RomajiRule, RomajiTable,
+  RuleTable,
+};
 pub use unknown_word::UnknownWordTransformer;
 pub use word::Word;
 pub use yomi::YomiTransformer;
@@ -41,6 +48,10 @@ pub enum BufferState {
 pub trait Displayable {
   fn buffer_content(&self) -> String;
   fn display_string(&self) -> String;
+  // display_stringの中のカーソルの位置を文字数で返す
+  fn cursor_offset(&self) -> usize {
+    self.display_string().chars().count()
+  }
   fn is_empty(&self) -> bool {
     self.buffer_content().len() == 0
   }
@@ -69,7 +80,7 @@ pub trait WithContext {
 }
 
 pub trait Transformable:
-  AsTransformerTrait + Displayable + Stackable + WithContext + objekt::Clone
+  AsTransformerTrait + Displayable + Stackable + WithContext + objekt::Clone + Send
 {
   fn transformer_type(&self) -> TransformerTypes;
   fn is_base_transformer(&self) -> bool {
@@ -117,6 +128,10 @@ pub trait Transformable:
     box StoppedTransformer::canceled(self.clone_context())
   }
 
+  fn to_completed_with_literal(&self, character: char) -> Box<dyn Transformable> {
+    self.to_completed_with_update_buffer(character.to_string())
+  }
+
   fn push_key(&self, key: &KeyCode) -> Option<Box<dyn Transformable>> {
     println!(
       "{}",
@@ -167,6 +182,10 @@ pub trait Transformable:
       KeyCode::Meta(MetaKey::ArrowDown) => self.push_arrow_down(),
       KeyCode::Meta(MetaKey::ArrowLeft) => self.push_arrow_left(),
       KeyCode::Meta(MetaKey::ArrowUp) => self.push_arrow_up(),
+      KeyCode::Meta(MetaKey::Home) => self.push_home(),
+      KeyCode::Meta(MetaKey::End) => self.push_end(),
+      KeyCode::Meta(MetaKey::PageUp) => self.push_page_up(),
+      KeyCode::Meta(MetaKey::PageDown) => self.push_page_down(),
       _ => self.push_any_character(key_code),
     }
   }
@@ -205,12 +224,34 @@ pub trait Transformable:
   fn push_arrow_up(&self) -> Option<Vec<Box<dyn Transformable>>> {
     None
   }
+  fn push_home(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    None
+  }
+  fn push_end(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    None
+  }
+  fn push_page_up(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    None
+  }
+  fn push_page_down(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    None
+  }
   fn push_any_character(&self, _: &KeyCode) -> Option<Vec<Box<dyn Transformable>>> {
     None
   }
 }
 
 impl dyn Transformable {
+  // 入れ子の変換器をたどり、候補を選択しているか調べる
+  pub fn is_selecting_candidate(&self) -> bool {
+    let target = self.send_target();
+    match target.transformer_type() {
+      TransformerTypes::SelectCandidate => true,
+      transformer_type if transformer_type == self.transformer_type() => false,
+      _ => target.is_selecting_candidate(),
+    }
+  }
+
   #[allow(unused_must_use)]
   fn print_stack(&self, f: &mut fmt::Formatter<'_>, depth: usize) {
     let indent = "\t".repeat(depth);
@@ -12,25 +12,45 @@ pub struct SelectCandidateTransformer {
   buffer_state: BufferState,
   dictionary_entry: DictionaryEntry,
   candidates: Candidates,
+  // 変換時に生成した候補。確定するときに作り直すと時刻などが変わるため、ここで持つ
+  dynamic_candidates: Vec<Candidate>,
   word: Word,
 }
 
 impl SelectCandidateTransformer {
-  pub fn new(context: Context, dictionary_entry: &DictionaryEntry, word: Word) -> Self {
+  pub fn new(
+    context: Context,
+    dictionary_entry: &DictionaryEntry,
+    dynamic_candidates: &Vec<Candidate>,
+    word: Word,
+  ) -> Self {
+    let candidates = context.rank_candidates(dictionary_entry);
+
     SelectCandidateTransformer {
       context,
       buffer: "".to_string(),
       buffer_state: BufferState::Continue,
       dictionary_entry: dictionary_entry.clone(),
-      candidates: Candidates::new(&dictionary_entry.candidates),
+      candidates: Candidates::new(&candidates),
+      dynamic_candidates: dynamic_candidates.clone(),
       word,
     }
   }
 
   fn try_transition_to_stopped(&self) -> Option<Box<dyn Transformable>> {
-    self.candidates.current().and(Some(
-      self.to_completed_with_update_buffer(self.buffer_content()),
-    ))
+    let candidate = self.candidates.current()?;
+    let read = &self.dictionary_entry.read;
+    let mut tf = self.clone();
+    // プライベートモードで確定した語は、後でモードを切り替えても辞書の更新にしない
+    if !self.dynamic_candidates.contains(candidate) && !self.context.is_private_mode() {
+      let context = tf.push_dictionary_updates(&vec![DictionaryEntry::new(
+        read.clone(),
+        vec![candidate.clone()],
+      )]);
+      tf.set_context(context);
+    }
+
+    Some(tf.to_completed_with_update_buffer(self.buffer_content()))
   }
 
   fn append_okuri(&self) -> Option<String> {
@@ -184,8 +204,11 @@ impl Candidates {
 mod tests {
   use super::super::tables::LetterType;
   use super::*;
-  use crate::tests::dummy_context;
+  use crate::composition::Composition;
+  use crate::dictionary::RankingPolicy;
+  use crate::tests::{dummy_context, dummy_context_with, str_to_key_code_vector};
   use crate::transformers::StoppedReason::*;
+  use crate::RSKKConfig;
   use TransformerTypes::*;
 
   #[test]
@@ -197,6 +220,7 @@ mod tests {
     let tf = SelectCandidateTransformer::new(
       conf,
       &DictionaryEntry::new("test", vec),
+      &vec![],
       Word::from((LetterType::Hiragana, "michigo")),
     );
 
@@ -215,6 +239,61 @@ mod tests {
     crate::tests::helpers::TestData::batch(vec);
   }
 
+  #[test]
+  fn dictionary_updates() {
+    let conf = dummy_context();
+    let entry = DictionaryEntry::new(
+      "test",
+      vec![Candidate::new("a", None), Candidate::new("b", None)],
+    );
+    let tf: Box<dyn Transformable> = box SelectCandidateTransformer::new(
+      conf.clone(),
+      &entry,
+      &vec![],
+      Word::from((LetterType::Hiragana, "michigo")),
+    );
+
+    let mut composition = Composition::new_from_transformer(conf.clone(), tf);
+    str_to_key_code_vector(" \n").iter().for_each(|event| {
+      composition.push_key_event(event);
+    });
+    assert_eq!(
+      composition.result().dictionary_updates(),
+      &vec![DictionaryEntry::new(
+        "test",
+        vec![Candidate::new("b", None)]
+      )]
+    );
+  }
+
+  #[test]
+  fn ranking() {
+    let entry = DictionaryEntry::new(
+      "test",
+      vec![Candidate::new("a", None), Candidate::new("b", None)],
+    );
+    let mut config = RSKKConfig::default_config();
+    config.candidate_ranking = RankingPolicy::MostRecentlyUsed;
+    let conf = dummy_context_with(config);
+    conf.statistics().write().unwrap().record_usage(
+      &DictionaryEntry::new("test", vec![Candidate::new("b", None)]),
+      100,
+    );
+
+    let tf = SelectCandidateTransformer::new(
+      conf,
+      &entry,
+      &vec![],
+      Word::from((LetterType::Hiragana, "test")),
+    );
+    let vec = crate::tds![tf;
+      ["", { display: "▼b", transformer_type: SelectCandidate }],
+      [" ", { display: "▼a", transformer_type: SelectCandidate }],
+      [" \n", { stopped_buffer: "a", transformer_type: Stopped(Compleated) }],
+    ];
+    crate::tests::helpers::TestData::batch(vec);
+  }
+
   mod candidates {
     use super::*;
 
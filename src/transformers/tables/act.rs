@@ -0,0 +1,44 @@
+// ACT (Dvorak配列向けにAZIKの考え方を適用したもの)
+// 拡張キーをDvorak配列のホームポジション付近に置いている
+//   ;: 〜あん  n: 〜いん  h: 〜うん  t: 〜えん  s: 〜おん
+//   ': 〜あい  p: 〜うう  .: 〜えい  ,: 〜おう
+// 単独の「q」は「ん」、「;」は「っ」、「c」はか行で、「[」はかなの切り替えに使う
+use super::extended_romaji::{ExtendedRomaji, Extension};
+
+pub const EXTENSIONS: &[(char, Extension)] = &[
+  (';', Extension::Nasal('a')),
+  ('n', Extension::Nasal('i')),
+  ('h', Extension::Nasal('u')),
+  ('t', Extension::Nasal('e')),
+  ('s', Extension::Nasal('o')),
+  ('\'', Extension::Diphthong('a', "い")),
+  ('p', Extension::Diphthong('u', "う")),
+  ('.', Extension::Diphthong('e', "い")),
+  (',', Extension::Diphthong('o', "う")),
+];
+
+pub const RULES: &[(&str, &str, &str)] = &[
+  ("q", "ん", ""),
+  (";", "っ", ""),
+  ("ca", "か", ""),
+  ("ci", "き", ""),
+  ("cu", "く", ""),
+  ("ce", "け", ""),
+  ("co", "こ", ""),
+];
+
+pub const REMOVED: &[&str] = &[
+  "[", "cha", "chi", "chu", "che", "cho", "cya", "cyi", "cyu", "cye", "cyo", "sha", "shi", "shu",
+  "she", "sho", "tha", "thi", "thu", "the", "tho", "dha", "dhi", "dhu", "dhe", "dho", "tsa", "tsi",
+  "tsu", "tse", "tso",
+];
+
+pub const LAYOUT: ExtendedRomaji = ExtendedRomaji {
+  extensions: EXTENSIONS,
+  consonants: &[
+    "k", "c", "s", "t", "n", "h", "m", "r", "g", "z", "d", "b", "p", "ky", "sy", "ty", "ny", "hy",
+    "my", "ry", "gy", "zy", "by", "py", "j",
+  ],
+  rules: RULES,
+  removed: REMOVED,
+};
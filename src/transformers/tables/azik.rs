@@ -0,0 +1,44 @@
+// AZIK (QWERTY配列向けの拡張ローマ字入力)
+// 子音の後ろに置く拡張キーで撥音と二重母音をまとめて入力する
+//   z, n: 〜あん  k: 〜いん  j: 〜うん  d: 〜えん  l: 〜おん
+//   q: 〜あい  h: 〜うう  w: 〜えい  p: 〜おう
+// 単独の「q」は「ん」、「;」は「っ」、「x」はsh行で、「[」はかなの切り替えに使う
+use super::extended_romaji::{ExtendedRomaji, Extension};
+
+pub const EXTENSIONS: &[(char, Extension)] = &[
+  ('z', Extension::Nasal('a')),
+  ('n', Extension::Nasal('a')),
+  ('k', Extension::Nasal('i')),
+  ('j', Extension::Nasal('u')),
+  ('d', Extension::Nasal('e')),
+  ('l', Extension::Nasal('o')),
+  ('q', Extension::Diphthong('a', "い")),
+  ('h', Extension::Diphthong('u', "う")),
+  ('w', Extension::Diphthong('e', "い")),
+  ('p', Extension::Diphthong('o', "う")),
+];
+
+pub const RULES: &[(&str, &str, &str)] = &[
+  ("q", "ん", ""),
+  (";", "っ", ""),
+  ("xa", "しゃ", ""),
+  ("xi", "し", ""),
+  ("xu", "しゅ", ""),
+  ("xe", "しぇ", ""),
+  ("xo", "しょ", ""),
+];
+
+pub const REMOVED: &[&str] = &[
+  "[", "xya", "xyi", "xyu", "xye", "xyo", "sha", "shi", "shu", "she", "sho", "tha", "thi", "thu",
+  "the", "tho", "dha", "dhi", "dhu", "dhe", "dho",
+];
+
+pub const LAYOUT: ExtendedRomaji = ExtendedRomaji {
+  extensions: EXTENSIONS,
+  consonants: &[
+    "k", "s", "t", "n", "h", "m", "r", "g", "z", "d", "b", "p", "x", "ky", "sy", "ty", "ny", "hy",
+    "my", "ry", "gy", "zy", "by", "py", "j",
+  ],
+  rules: RULES,
+  removed: REMOVED,
+};
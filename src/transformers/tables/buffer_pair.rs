@@ -1,7 +1,8 @@
 use super::direct;
-use super::hiragana;
+use super::em_eisu;
+use super::en_katakana;
 use super::katakana;
-use super::{BufferState, LetterType};
+use super::{BufferState, LetterType, RuleTable};
 use LetterType::*;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -24,16 +25,38 @@ impl BufferPair {
     Self::new(letter_type, "", BufferState::Continue)
   }
 
-  pub fn push(&self, character: char) -> Option<Vec<BufferPair>> {
+  pub fn push(&self, rules: &RuleTable, character: char) -> Option<Vec<BufferPair>> {
     match &self.letter_type {
       &Direct => direct::convert(&self.buffer, character),
-      &Hiragana => hiragana::convert(&self.buffer, character),
-      &Katakana => katakana::convert(&self.buffer, character),
-      &EnKatakana => unimplemented!(),
-      &EmEisu => unimplemented!(),
+      &EmEisu => em_eisu::convert(&self.buffer, character),
+      &Hiragana | &Katakana | &EnKatakana => {
+        Some(self.to_pairs(rules.convert(&self.buffer, character)?))
+      }
     }
   }
 
+  pub fn flush(&self, rules: &RuleTable) -> Option<Vec<BufferPair>> {
+    match &self.letter_type {
+      &Hiragana | &Katakana | &EnKatakana => Some(self.to_pairs(rules.flush(&self.buffer)?)),
+      _ => None,
+    }
+  }
+
+  fn to_pairs(&self, pairs: Vec<(String, BufferState)>) -> Vec<BufferPair> {
+    pairs
+      .into_iter()
+      .map(|(buffer, state)| match (self.letter_type, state) {
+        (Katakana, BufferState::Stop) => {
+          BufferPair::new(Katakana, katakana::from_hiragana(&buffer), state)
+        }
+        (EnKatakana, BufferState::Stop) => {
+          BufferPair::new(EnKatakana, en_katakana::from_kana(&buffer), state)
+        }
+        _ => BufferPair::new(self.letter_type, buffer, state),
+      })
+      .collect()
+  }
+
   pub fn is_stopped(&self) -> bool {
     self.state == BufferState::Stop
   }
@@ -69,42 +92,53 @@ mod tests {
 
   #[test]
   fn push() {
+    let rules = RuleTable::default_rules();
     let pair = BufferPair::new_empty(Hiragana);
     assert_eq!(
-      pair.push('a'),
+      pair.push(&rules, 'a'),
       Some(vec![BufferPair::new(Hiragana, "あ", Stop)])
     );
 
     let pair = BufferPair::new_empty(Hiragana);
     assert_eq!(
-      pair.push('t'),
+      pair.push(&rules, 't'),
       Some(vec![BufferPair::new(Hiragana, "t", Continue)])
     );
 
     let pair = BufferPair::new(Hiragana, "t", Continue);
     assert_eq!(
-      pair.push('a'),
+      pair.push(&rules, 'a'),
       Some(vec![BufferPair::new(Hiragana, "た", Stop)])
     );
 
     let pair = BufferPair::new(Hiragana, "t", Continue);
     assert_eq!(
-      pair.push('t'),
+      pair.push(&rules, 't'),
       Some(vec![
         BufferPair::new(Hiragana, "っ", Stop),
         BufferPair::new(Hiragana, "t", Continue)
       ])
     );
+
+    let pair = BufferPair::new(Katakana, "ky", Continue);
+    assert_eq!(
+      pair.push(&rules, 'a'),
+      Some(vec![
+        BufferPair::new(Katakana, "キ", Stop),
+        BufferPair::new(Katakana, "ャ", Stop)
+      ])
+    );
   }
 
   #[test]
   fn remove_last() {
+    let rules = RuleTable::default_rules();
     let mut pair = BufferPair::new_empty(Hiragana);
     assert_eq!(pair.remove_last(), None);
     assert_eq!(pair.buffer, "");
 
     let mut pair = BufferPair::new_empty(Hiragana)
-      .push('a')
+      .push(&rules, 'a')
       .unwrap()
       .pop()
       .unwrap();
@@ -112,7 +146,7 @@ mod tests {
     assert_eq!(pair.buffer, "");
 
     let mut pair = BufferPair::new_empty(Hiragana)
-      .push('t')
+      .push(&rules, 't')
       .unwrap()
       .pop()
       .unwrap();
@@ -120,11 +154,11 @@ mod tests {
     assert_eq!(pair.buffer, "");
 
     let mut pair = BufferPair::new_empty(Hiragana)
-      .push('t')
+      .push(&rules, 't')
       .unwrap()
       .pop()
       .unwrap()
-      .push('s')
+      .push(&rules, 's')
       .unwrap()
       .pop()
       .unwrap();
@@ -1,16 +1,23 @@
-use super::{BufferPair, BufferState, LetterType};
+use super::{BufferPair, BufferState, LetterType, RuleTable};
+use std::sync::Arc;
 
 #[derive(Clone, Debug)]
 pub struct BufferPairs {
   buffer: Vec<BufferPair>,
   letter_type: LetterType,
+  rules: Arc<RuleTable>,
 }
 
 impl BufferPairs {
   pub fn new(letter_type: LetterType) -> Self {
+    Self::new_with_rules(letter_type, RuleTable::default_rules())
+  }
+
+  pub fn new_with_rules(letter_type: LetterType, rules: Arc<RuleTable>) -> Self {
     BufferPairs {
       buffer: vec![],
       letter_type,
+      rules,
     }
   }
 
@@ -21,13 +28,26 @@ impl BufferPairs {
       _ => {}
     }
 
+    let rules = self.rules.clone();
     self.buffer.pop().and_then(|pair| {
       pair
-        .push(character)
+        .push(&rules, character)
         .map(|vec| vec.into_iter().for_each(|pair| self.push_pair(pair)))
     });
   }
 
+  pub fn flush(&mut self) {
+    let pairs = match self.buffer.last() {
+      Some(pair) if !pair.is_stopped() => pair.flush(&self.rules),
+      _ => None,
+    };
+
+    if let Some(pairs) = pairs {
+      self.buffer.pop();
+      pairs.into_iter().for_each(|pair| self.push_pair(pair));
+    }
+  }
+
   pub fn is_stopped(&self) -> bool {
     self
       .buffer
@@ -36,6 +56,11 @@ impl BufferPairs {
       .unwrap_or(false)
   }
 
+  // かな入力の送り仮名では、先頭のかなが確定した時点で送り仮名の入力を終える
+  pub fn has_stopped(&self) -> bool {
+    self.buffer.iter().any(|item| item.is_stopped())
+  }
+
   pub fn remove_last(&mut self) -> Option<BufferPair> {
     let mut pair = self.buffer.pop()?;
     match pair.state() {
@@ -52,6 +77,27 @@ impl BufferPairs {
     }
   }
 
+  // ▽のカーソル移動に使う。末尾の確定した文字を、otherの先頭に移す
+  pub fn move_last_to(&mut self, other: &mut BufferPairs) -> bool {
+    match self.buffer.last() {
+      Some(pair) if pair.is_stopped() => {
+        other.buffer.insert(0, self.buffer.pop().unwrap());
+        true
+      }
+      _ => false,
+    }
+  }
+
+  pub fn move_first_to(&mut self, other: &mut BufferPairs) -> bool {
+    match self.buffer.is_empty() {
+      true => false,
+      false => {
+        other.push_pair(self.buffer.remove(0));
+        true
+      }
+    }
+  }
+
   pub fn to_string(&self) -> String {
     self
       .buffer
@@ -67,6 +113,25 @@ impl BufferPairs {
     self.letter_type
   }
 
+  pub fn rules(&self) -> Arc<RuleTable> {
+    self.rules.clone()
+  }
+
+  pub fn claims(&self, character: char) -> bool {
+    self.rules.claims(&self.pending(), character)
+  }
+
+  pub fn continues(&self, character: char) -> bool {
+    self.rules.continues(&self.pending(), character)
+  }
+
+  fn pending(&self) -> String {
+    match self.buffer.last() {
+      Some(pair) if !pair.is_stopped() => pair.to_string(),
+      _ => String::new(),
+    }
+  }
+
   fn push_new_pair(&mut self) {
     self.buffer.push(BufferPair::new_empty(self.letter_type))
   }
@@ -78,8 +143,8 @@ impl BufferPairs {
   pub fn partition_by_state(&self) -> (BufferPairs, BufferPairs) {
     self.buffer.iter().fold(
       (
-        BufferPairs::new(self.letter_type()),
-        BufferPairs::new(self.letter_type()),
+        BufferPairs::new_with_rules(self.letter_type(), self.rules.clone()),
+        BufferPairs::new_with_rules(self.letter_type(), self.rules.clone()),
       ),
       |mut acc, pair| {
         match pair.state() {
@@ -108,9 +173,48 @@ impl From<(LetterType, &str)> for BufferPairs {
 
 #[cfg(test)]
 mod tests {
+  use super::super::{hiragana, katakana};
   use super::*;
   use LetterType::*;
 
+  #[test]
+  fn every_rule() {
+    let sokuon = hiragana::SOKUON
+      .iter()
+      .map(|c| (format!("{}{}", c, c), "っ".to_owned(), c.to_string()))
+      .collect::<Vec<_>>();
+    let rules = hiragana::RULES
+      .iter()
+      .map(|(input, output, next)| (input.to_string(), output.to_string(), next.to_string()))
+      .chain(sokuon)
+      .collect::<Vec<_>>();
+
+    rules.iter().for_each(|(input, output, next)| {
+      // 「n」のように続く入力で確定するルールは、ルールにない文字を続けて確定させる
+      let rest = match rules
+        .iter()
+        .any(|(other, _, _)| other != input && other.starts_with(input as &str))
+      {
+        true => "1",
+        false => "",
+      };
+      let input = input.clone() + rest;
+
+      assert_eq!(
+        BufferPairs::from((Hiragana, &input as &str)).to_string(),
+        output.clone() + next + rest,
+        "{}",
+        input
+      );
+      assert_eq!(
+        BufferPairs::from((Katakana, &input as &str)).to_string(),
+        katakana::from_hiragana(output) + next + rest,
+        "{}",
+        input
+      );
+    });
+  }
+
   #[test]
   fn is_stopped() {
     assert_eq!(BufferPairs::from((Hiragana, "")).is_stopped(), false);
@@ -135,6 +239,23 @@ mod tests {
     );
   }
 
+  #[test]
+  fn move_last_to() {
+    let mut pairs = BufferPairs::from((Hiragana, "kanj"));
+    let mut after = BufferPairs::new(Hiragana);
+    assert!(!pairs.move_last_to(&mut after));
+    let mut pairs = BufferPairs::from((Hiragana, "kan"));
+    pairs.flush();
+    assert!(pairs.move_last_to(&mut after));
+    assert!(pairs.move_last_to(&mut after));
+    assert!(!pairs.move_last_to(&mut after));
+    assert_eq!(after.to_string(), "かん");
+
+    assert!(after.move_first_to(&mut pairs));
+    assert_eq!(pairs.to_string(), "か");
+    assert_eq!(after.to_string(), "ん");
+  }
+
   #[test]
   fn remove_last() {
     let mut pairs = BufferPairs::from((Hiragana, ""));
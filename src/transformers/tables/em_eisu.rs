@@ -0,0 +1,40 @@
+use super::super::BufferState::*;
+use super::{BufferPair, LetterType};
+use LetterType::*;
+
+// ASCIIの印字可能文字を全角に変換する。スペースは全角スペースになる
+pub fn convert(_: &str, character: char) -> Option<Vec<BufferPair>> {
+  Some(vec![BufferPair::new(
+    EmEisu,
+    to_em_eisu(character).to_string(),
+    Stop,
+  )])
+}
+
+fn to_em_eisu(character: char) -> char {
+  match character {
+    ' ' => '\u{3000}',
+    '!'..='~' => std::char::from_u32(character as u32 + 0xFEE0).unwrap_or(character),
+    _ => character,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn to_em_eisu() {
+    assert_eq!(super::to_em_eisu('a'), 'ａ');
+    assert_eq!(super::to_em_eisu('Z'), 'Ｚ');
+    assert_eq!(super::to_em_eisu('0'), '０');
+    assert_eq!(super::to_em_eisu('!'), '！');
+    assert_eq!(super::to_em_eisu('~'), '～');
+    assert_eq!(super::to_em_eisu(' '), '　');
+    assert_eq!(super::to_em_eisu('あ'), 'あ');
+    assert_eq!(
+      convert("", 'a'),
+      Some(vec![BufferPair::new(EmEisu, "ａ", Stop)])
+    );
+  }
+}
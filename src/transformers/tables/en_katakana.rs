@@ -0,0 +1,129 @@
+// JIS X 0201の半角カタカナ。濁音・半濁音は濁点を後ろに付けた2文字になる
+const TABLE: &[(char, &str)] = &[
+  ('ぁ', "ｧ"),
+  ('あ', "ｱ"),
+  ('ぃ', "ｨ"),
+  ('い', "ｲ"),
+  ('ぅ', "ｩ"),
+  ('う', "ｳ"),
+  ('ぇ', "ｪ"),
+  ('え', "ｴ"),
+  ('ぉ', "ｫ"),
+  ('お', "ｵ"),
+  ('か', "ｶ"),
+  ('が', "ｶﾞ"),
+  ('き', "ｷ"),
+  ('ぎ', "ｷﾞ"),
+  ('く', "ｸ"),
+  ('ぐ', "ｸﾞ"),
+  ('け', "ｹ"),
+  ('げ', "ｹﾞ"),
+  ('こ', "ｺ"),
+  ('ご', "ｺﾞ"),
+  ('さ', "ｻ"),
+  ('ざ', "ｻﾞ"),
+  ('し', "ｼ"),
+  ('じ', "ｼﾞ"),
+  ('す', "ｽ"),
+  ('ず', "ｽﾞ"),
+  ('せ', "ｾ"),
+  ('ぜ', "ｾﾞ"),
+  ('そ', "ｿ"),
+  ('ぞ', "ｿﾞ"),
+  ('た', "ﾀ"),
+  ('だ', "ﾀﾞ"),
+  ('ち', "ﾁ"),
+  ('ぢ', "ﾁﾞ"),
+  ('っ', "ｯ"),
+  ('つ', "ﾂ"),
+  ('づ', "ﾂﾞ"),
+  ('て', "ﾃ"),
+  ('で', "ﾃﾞ"),
+  ('と', "ﾄ"),
+  ('ど', "ﾄﾞ"),
+  ('な', "ﾅ"),
+  ('に', "ﾆ"),
+  ('ぬ', "ﾇ"),
+  ('ね', "ﾈ"),
+  ('の', "ﾉ"),
+  ('は', "ﾊ"),
+  ('ば', "ﾊﾞ"),
+  ('ぱ', "ﾊﾟ"),
+  ('ひ', "ﾋ"),
+  ('び', "ﾋﾞ"),
+  ('ぴ', "ﾋﾟ"),
+  ('ふ', "ﾌ"),
+  ('ぶ', "ﾌﾞ"),
+  ('ぷ', "ﾌﾟ"),
+  ('へ', "ﾍ"),
+  ('べ', "ﾍﾞ"),
+  ('ぺ', "ﾍﾟ"),
+  ('ほ', "ﾎ"),
+  ('ぼ', "ﾎﾞ"),
+  ('ぽ', "ﾎﾟ"),
+  ('ま', "ﾏ"),
+  ('み', "ﾐ"),
+  ('む', "ﾑ"),
+  ('め', "ﾒ"),
+  ('も', "ﾓ"),
+  ('ゃ', "ｬ"),
+  ('や', "ﾔ"),
+  ('ゅ', "ｭ"),
+  ('ゆ', "ﾕ"),
+  ('ょ', "ｮ"),
+  ('よ', "ﾖ"),
+  ('ら', "ﾗ"),
+  ('り', "ﾘ"),
+  ('る', "ﾙ"),
+  ('れ', "ﾚ"),
+  ('ろ', "ﾛ"),
+  // 半角に無い文字は近い文字で代用する
+  ('ゎ', "ﾜ"),
+  ('わ', "ﾜ"),
+  ('ゐ', "ｲ"),
+  ('ゑ', "ｴ"),
+  ('を', "ｦ"),
+  ('ん', "ﾝ"),
+  ('ゔ', "ｳﾞ"),
+  ('ゕ', "ｶ"),
+  ('ゖ', "ｹ"),
+  // 記号類
+  ('。', "｡"),
+  ('「', "｢"),
+  ('」', "｣"),
+  ('、', "､"),
+  ('・', "･"),
+  ('ー', "ｰ"),
+  ('゛', "ﾞ"),
+  ('゜', "ﾟ"),
+];
+
+// ひらがなとカタカナを半角カタカナに変換し、それ以外の文字はそのまま残す
+pub fn from_kana(string: &str) -> String {
+  string.chars().fold(String::new(), |acc, c| {
+    let hiragana = match c {
+      '\u{30A1}'..='\u{30F6}' => std::char::from_u32(c as u32 - 0x60).unwrap_or(c),
+      _ => c,
+    };
+
+    match TABLE.iter().find(|(kana, _)| *kana == hiragana) {
+      Some((_, half)) => acc + half,
+      None => acc + &c.to_string(),
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn from_kana() {
+    assert_eq!(super::from_kana("かたかな"), "ｶﾀｶﾅ");
+    assert_eq!(super::from_kana("カタカナ"), "ｶﾀｶﾅ");
+    assert_eq!(super::from_kana("がっぴょう"), "ｶﾞｯﾋﾟｮｳ");
+    assert_eq!(super::from_kana("ゔぁ、ー。「」"), "ｳﾞｧ､ｰ｡｢｣");
+    assert_eq!(super::from_kana("kｶ漢"), "kｶ漢");
+    assert_eq!(TABLE.iter().filter(|(_, half)| half.is_empty()).count(), 0);
+  }
+}
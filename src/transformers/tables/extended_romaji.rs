@@ -0,0 +1,42 @@
+use super::RuleTable;
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Extension {
+  // 母音の後ろに「ん」を付ける
+  Nasal(char),
+  // 母音の後ろに「い」や「う」を付ける
+  Diphthong(char, &'static str),
+}
+
+// AZIKやACTのように、既定のルールに子音と拡張キーの組み合わせを加えたもの
+pub struct ExtendedRomaji {
+  pub extensions: &'static [(char, Extension)],
+  pub consonants: &'static [&'static str],
+  pub rules: &'static [(&'static str, &'static str, &'static str)],
+  pub removed: &'static [&'static str],
+}
+
+impl ExtendedRomaji {
+  pub fn apply(&self, table: &mut RuleTable) {
+    self.removed.iter().for_each(|input| table.remove(input));
+    self
+      .rules
+      .iter()
+      .for_each(|(input, output, next)| table.insert(input, output, next));
+
+    self.consonants.iter().for_each(|consonant| {
+      self.extensions.iter().for_each(|(key, extension)| {
+        let (vowel, suffix) = match extension {
+          Extension::Nasal(vowel) => (vowel, "ん"),
+          Extension::Diphthong(vowel, suffix) => (vowel, *suffix),
+        };
+        let output = match table.get(&format!("{}{}", consonant, vowel)) {
+          Some(rule) => rule.output.clone() + suffix,
+          None => return,
+        };
+
+        table.insert(&format!("{}{}", consonant, key), &output, "");
+      })
+    });
+  }
+}
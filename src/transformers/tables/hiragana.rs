@@ -1,386 +1,267 @@
-use super::super::BufferState::*;
-use super::{BufferPair, LetterType};
-use LetterType::*;
-
-pub fn convert(current_buffer: &str, character: char) -> Option<Vec<BufferPair>> {
-  let character = character.to_lowercase().next()?;
-
-  let pairs = match (current_buffer, character) {
-    ("", 'a') => Some(vec![("あ", Stop)]),
-    ("", 'i') => Some(vec![("い", Stop)]),
-    ("", 'u') => Some(vec![("う", Stop)]),
-    ("", 'e') => Some(vec![("え", Stop)]),
-    ("", 'o') => Some(vec![("お", Stop)]),
-
-    ("", 'k') => Some(vec![("k", Continue)]),
-    ("k", 'a') => Some(vec![("か", Stop)]),
-    ("k", 'i') => Some(vec![("き", Stop)]),
-    ("k", 'u') => Some(vec![("く", Stop)]),
-    ("k", 'e') => Some(vec![("け", Stop)]),
-    ("k", 'o') => Some(vec![("こ", Stop)]),
-    ("k", 'y') => Some(vec![("ky", Continue)]),
-    ("ky", 'a') => Some(vec![("き", Stop), ("ゃ", Stop)]),
-    ("ky", 'i') => Some(vec![("き", Stop), ("ぃ", Stop)]),
-    ("ky", 'u') => Some(vec![("き", Stop), ("ゅ", Stop)]),
-    ("ky", 'e') => Some(vec![("き", Stop), ("ぇ", Stop)]),
-    ("ky", 'o') => Some(vec![("き", Stop), ("ょ", Stop)]),
-
-    ("", 'g') => Some(vec![("g", Continue)]),
-    ("g", 'a') => Some(vec![("が", Stop)]),
-    ("g", 'i') => Some(vec![("ぎ", Stop)]),
-    ("g", 'u') => Some(vec![("ぐ", Stop)]),
-    ("g", 'e') => Some(vec![("げ", Stop)]),
-    ("g", 'o') => Some(vec![("ご", Stop)]),
-    ("g", 'y') => Some(vec![("gy", Continue)]),
-    ("gy", 'a') => Some(vec![("ぎ", Stop), ("ゃ", Stop)]),
-    ("gy", 'i') => Some(vec![("ぎ", Stop), ("ぃ", Stop)]),
-    ("gy", 'u') => Some(vec![("ぎ", Stop), ("ゅ", Stop)]),
-    ("gy", 'e') => Some(vec![("ぎ", Stop), ("ぇ", Stop)]),
-    ("gy", 'o') => Some(vec![("ぎ", Stop), ("ょ", Stop)]),
-
-    ("", 's') => Some(vec![("s", Continue)]),
-    ("s", 'a') => Some(vec![("さ", Stop)]),
-    ("s", 'i') => Some(vec![("し", Stop)]),
-    ("s", 'u') => Some(vec![("す", Stop)]),
-    ("s", 'e') => Some(vec![("せ", Stop)]),
-    ("s", 'o') => Some(vec![("そ", Stop)]),
-    ("s", 'y') => Some(vec![("sy", Continue)]),
-    ("sy", 'a') => Some(vec![("し", Stop), ("ゃ", Stop)]),
-    ("sy", 'i') => Some(vec![("し", Stop), ("ぃ", Stop)]),
-    ("sy", 'u') => Some(vec![("し", Stop), ("ゅ", Stop)]),
-    ("sy", 'e') => Some(vec![("し", Stop), ("ぇ", Stop)]),
-    ("sy", 'o') => Some(vec![("し", Stop), ("ょ", Stop)]),
-    ("s", 'h') => Some(vec![("sh", Continue)]),
-    ("sh", 'a') => Some(vec![("しゃ", Stop), ("ゃ", Stop)]),
-    ("sh", 'i') => Some(vec![("し", Stop)]),
-    ("sh", 'u') => Some(vec![("しゅ", Stop), ("ゅ", Stop)]),
-    ("sh", 'e') => Some(vec![("しぇ", Stop), ("ぇ", Stop)]),
-    ("sh", 'o') => Some(vec![("しょ", Stop), ("ょ", Stop)]),
-
-    ("", 'z') => Some(vec![("z", Continue)]),
-    ("z", 'a') => Some(vec![("ざ", Stop)]),
-    ("z", 'i') => Some(vec![("じ", Stop)]),
-    ("z", 'u') => Some(vec![("ず", Stop)]),
-    ("z", 'e') => Some(vec![("ぜ", Stop)]),
-    ("z", 'o') => Some(vec![("ぞ", Stop)]),
-    ("z", 'y') => Some(vec![("gy", Continue)]),
-    ("zy", 'a') => Some(vec![("じ", Stop), ("ゃ", Stop)]),
-    ("zy", 'i') => Some(vec![("じ", Stop), ("ぃ", Stop)]),
-    ("zy", 'u') => Some(vec![("じ", Stop), ("ゅ", Stop)]),
-    ("zy", 'e') => Some(vec![("じ", Stop), ("ぇ", Stop)]),
-    ("zy", 'o') => Some(vec![("じ", Stop), ("ょ", Stop)]),
-
-    ("", 't') => Some(vec![("t", Continue)]),
-    ("t", 'a') => Some(vec![("た", Stop)]),
-    ("t", 'i') => Some(vec![("ち", Stop)]),
-    ("t", 'u') => Some(vec![("つ", Stop)]),
-    ("t", 'e') => Some(vec![("て", Stop)]),
-    ("t", 'o') => Some(vec![("と", Stop)]),
-    ("t", 'y') => Some(vec![("ty", Continue)]),
-    ("ty", 'a') => Some(vec![("ち", Stop), ("ゃ", Stop)]),
-    ("ty", 'i') => Some(vec![("ち", Stop), ("ぃ", Stop)]),
-    ("ty", 'u') => Some(vec![("ち", Stop), ("ゅ", Stop)]),
-    ("ty", 'e') => Some(vec![("ち", Stop), ("ぇ", Stop)]),
-    ("ty", 'o') => Some(vec![("ち", Stop), ("ょ", Stop)]),
-    ("t", 'h') => Some(vec![("th", Continue)]),
-    ("th", 'a') => Some(vec![("て", Stop), ("ぁ", Stop)]),
-    ("th", 'i') => Some(vec![("て", Stop), ("ぃ", Stop)]),
-    ("th", 'u') => Some(vec![("て", Stop), ("ゅ", Stop)]),
-    ("th", 'e') => Some(vec![("て", Stop), ("ぇ", Stop)]),
-    ("th", 'o') => Some(vec![("て", Stop), ("ょ", Stop)]),
-
-    ("t", 's') => Some(vec![("ts", Continue)]),
-    ("ts", 'a') => Some(vec![("つ", Stop), ("ぁ", Stop)]),
-    ("ts", 'i') => Some(vec![("つ", Stop), ("ぃ", Stop)]),
-    ("ts", 'u') => Some(vec![("つ", Stop)]),
-    ("ts", 'e') => Some(vec![("つ", Stop), ("ぇ", Stop)]),
-    ("ts", 'o') => Some(vec![("つ", Stop), ("ぉ", Stop)]),
-
-    ("", 'd') => Some(vec![("d", Continue)]),
-    ("d", 'a') => Some(vec![("だ", Stop)]),
-    ("d", 'i') => Some(vec![("ぢ", Stop)]),
-    ("d", 'u') => Some(vec![("づ", Stop)]),
-    ("d", 'e') => Some(vec![("で", Stop)]),
-    ("d", 'o') => Some(vec![("ど", Stop)]),
-    ("d", 'h') => Some(vec![("dh", Continue)]),
-    ("dh", 'a') => Some(vec![("で", Stop), ("ゃ", Stop)]),
-    ("dh", 'i') => Some(vec![("で", Stop), ("ぃ", Stop)]),
-    ("dh", 'u') => Some(vec![("で", Stop), ("ゅ", Stop)]),
-    ("dh", 'e') => Some(vec![("で", Stop), ("ぇ", Stop)]),
-    ("dh", 'o') => Some(vec![("で", Stop), ("ょ", Stop)]),
-    ("d", 'y') => Some(vec![("dy", Continue)]),
-    ("dy", 'a') => Some(vec![("ぢ", Stop), ("ゃ", Stop)]),
-    ("dy", 'i') => Some(vec![("ぢ", Stop), ("ぃ", Stop)]),
-    ("dy", 'u') => Some(vec![("ぢ", Stop), ("ゅ", Stop)]),
-    ("dy", 'e') => Some(vec![("ぢ", Stop), ("ぇ", Stop)]),
-    ("dy", 'o') => Some(vec![("ぢ", Stop), ("ょ", Stop)]),
-
-    ("", 'c') => Some(vec![("c", Continue)]),
-    ("c", 'h') => Some(vec![("ch", Continue)]),
-    ("ch", 'a') => Some(vec![("ち", Stop), ("ゃ", Stop)]),
-    ("ch", 'i') => Some(vec![("ち", Stop)]),
-    ("ch", 'u') => Some(vec![("ち", Stop), ("ゅ", Stop)]),
-    ("ch", 'e') => Some(vec![("ち", Stop), ("ぇ", Stop)]),
-    ("ch", 'o') => Some(vec![("ち", Stop), ("ょ", Stop)]),
-    ("c", 'y') => Some(vec![("cy", Continue)]),
-    ("cy", 'a') => Some(vec![("ち", Stop), ("ゃ", Stop)]),
-    ("cy", 'i') => Some(vec![("ち", Stop), ("ぃ", Stop)]),
-    ("cy", 'u') => Some(vec![("ち", Stop), ("ゅ", Stop)]),
-    ("cy", 'e') => Some(vec![("ち", Stop), ("ぇ", Stop)]),
-    ("cy", 'o') => Some(vec![("ち", Stop), ("ょ", Stop)]),
-
-    ("", 'n') => Some(vec![("n", Continue)]),
-    ("n", 'a') => Some(vec![("な", Stop)]),
-    ("n", 'i') => Some(vec![("に", Stop)]),
-    ("n", 'u') => Some(vec![("ぬ", Stop)]),
-    ("n", 'e') => Some(vec![("ね", Stop)]),
-    ("n", 'o') => Some(vec![("の", Stop)]),
-    ("n", 'n') => Some(vec![("ん", Stop)]),
-    ("n", 'y') => Some(vec![("ny", Continue)]),
-    ("ny", 'a') => Some(vec![("に", Stop), ("ゃ", Stop)]),
-    ("ny", 'i') => Some(vec![("に", Stop), ("ぃ", Stop)]),
-    ("ny", 'u') => Some(vec![("に", Stop), ("ゅ", Stop)]),
-    ("ny", 'e') => Some(vec![("に", Stop), ("ぇ", Stop)]),
-    ("ny", 'o') => Some(vec![("に", Stop), ("ょ", Stop)]),
-
-    ("", 'h') => Some(vec![("h", Continue)]),
-    ("h", 'a') => Some(vec![("は", Stop)]),
-    ("h", 'i') => Some(vec![("ひ", Stop)]),
-    ("h", 'u') => Some(vec![("ふ", Stop)]),
-    ("h", 'e') => Some(vec![("へ", Stop)]),
-    ("h", 'o') => Some(vec![("ほ", Stop)]),
-    ("h", 'y') => Some(vec![("hy", Continue)]),
-    ("hy", 'a') => Some(vec![("ひ", Stop), ("ゃ", Stop)]),
-    ("hy", 'i') => Some(vec![("ひ", Stop), ("ぃ", Stop)]),
-    ("hy", 'u') => Some(vec![("ひ", Stop), ("ゅ", Stop)]),
-    ("hy", 'e') => Some(vec![("ひ", Stop), ("ぇ", Stop)]),
-    ("hy", 'o') => Some(vec![("ひ", Stop), ("ょ", Stop)]),
-
-    ("", 'p') => Some(vec![("p", Continue)]),
-    ("p", 'a') => Some(vec![("ぱ", Stop)]),
-    ("p", 'i') => Some(vec![("ぴ", Stop)]),
-    ("p", 'u') => Some(vec![("ぷ", Stop)]),
-    ("p", 'e') => Some(vec![("ぺ", Stop)]),
-    ("p", 'o') => Some(vec![("ぽ", Stop)]),
-    ("p", 'y') => Some(vec![("py", Continue)]),
-    ("py", 'a') => Some(vec![("ぴ", Stop), ("ゃ", Stop)]),
-    ("py", 'i') => Some(vec![("ぴ", Stop), ("ぃ", Stop)]),
-    ("py", 'u') => Some(vec![("ぴ", Stop), ("ゅ", Stop)]),
-    ("py", 'e') => Some(vec![("ぴ", Stop), ("ぇ", Stop)]),
-    ("py", 'o') => Some(vec![("ぴ", Stop), ("ょ", Stop)]),
-
-    ("", 'b') => Some(vec![("b", Continue)]),
-    ("b", 'a') => Some(vec![("ば", Stop)]),
-    ("b", 'i') => Some(vec![("び", Stop)]),
-    ("b", 'u') => Some(vec![("ぶ", Stop)]),
-    ("b", 'e') => Some(vec![("べ", Stop)]),
-    ("b", 'o') => Some(vec![("ぼ", Stop)]),
-    ("b", 'y') => Some(vec![("by", Continue)]),
-    ("by", 'a') => Some(vec![("び", Stop), ("ゃ", Stop)]),
-    ("by", 'i') => Some(vec![("び", Stop), ("ぃ", Stop)]),
-    ("by", 'u') => Some(vec![("び", Stop), ("ゅ", Stop)]),
-    ("by", 'e') => Some(vec![("び", Stop), ("ぇ", Stop)]),
-    ("by", 'o') => Some(vec![("び", Stop), ("ょ", Stop)]),
-
-    ("", 'f') => Some(vec![("f", Continue)]),
-    ("f", 'a') => Some(vec![("ふ", Stop), ("ぁ", Stop)]),
-    ("f", 'i') => Some(vec![("ふ", Stop), ("ぃ", Stop)]),
-    ("f", 'u') => Some(vec![("ふ", Stop)]),
-    ("f", 'e') => Some(vec![("ふ", Stop), ("ぇ", Stop)]),
-    ("f", 'o') => Some(vec![("ふ", Stop), ("ぉ", Stop)]),
-    ("f", 'y') => Some(vec![("fy", Continue)]),
-    ("fy", 'a') => Some(vec![("ふ", Stop), ("ゃ", Stop)]),
-    ("fy", 'i') => Some(vec![("ふ", Stop), ("ぃ", Stop)]),
-    ("fy", 'u') => Some(vec![("ふ", Stop), ("ゅ", Stop)]),
-    ("fy", 'e') => Some(vec![("ふ", Stop), ("ぇ", Stop)]),
-    ("fy", 'o') => Some(vec![("ふ", Stop), ("ょ", Stop)]),
-
-    ("", 'm') => Some(vec![("m", Continue)]),
-    ("m", 'a') => Some(vec![("ま", Stop)]),
-    ("m", 'i') => Some(vec![("み", Stop)]),
-    ("m", 'u') => Some(vec![("む", Stop)]),
-    ("m", 'e') => Some(vec![("め", Stop)]),
-    ("m", 'o') => Some(vec![("も", Stop)]),
-    ("m", 'y') => Some(vec![("my", Continue)]),
-    ("my", 'a') => Some(vec![("み", Stop), ("ゃ", Stop)]),
-    ("my", 'i') => Some(vec![("み", Stop), ("ぃ", Stop)]),
-    ("my", 'u') => Some(vec![("み", Stop), ("ゅ", Stop)]),
-    ("my", 'e') => Some(vec![("み", Stop), ("ぇ", Stop)]),
-    ("my", 'o') => Some(vec![("み", Stop), ("ょ", Stop)]),
-
-    ("", 'y') => Some(vec![("y", Continue)]),
-    ("y", 'a') => Some(vec![("や", Stop)]),
-    ("y", 'i') => Some(vec![("い", Stop)]),
-    ("y", 'u') => Some(vec![("ゆ", Stop)]),
-    ("y", 'e') => Some(vec![("い", Stop), ("ぇ", Stop)]),
-    ("y", 'o') => Some(vec![("よ", Stop)]),
-
-    ("", 'j') => Some(vec![("j", Continue)]),
-    ("j", 'a') => Some(vec![("じ", Stop), ("ゃ", Stop)]),
-    ("j", 'i') => Some(vec![("じ", Stop)]),
-    ("j", 'u') => Some(vec![("じ", Stop), ("ゅ", Stop)]),
-    ("j", 'e') => Some(vec![("じ", Stop), ("ぇ", Stop)]),
-    ("j", 'o') => Some(vec![("じ", Stop), ("ょ", Stop)]),
-    ("j", 'y') => Some(vec![("jy", Continue)]),
-    ("jy", 'a') => Some(vec![("じ", Stop), ("ゃ", Stop)]),
-    ("jy", 'i') => Some(vec![("じ", Stop), ("ぃ", Stop)]),
-    ("jy", 'u') => Some(vec![("じ", Stop), ("ゅ", Stop)]),
-    ("jy", 'e') => Some(vec![("じ", Stop), ("ぇ", Stop)]),
-    ("jy", 'o') => Some(vec![("じ", Stop), ("ょ", Stop)]),
-
-    ("", 'r') => Some(vec![("r", Continue)]),
-    ("r", 'a') => Some(vec![("ら", Stop)]),
-    ("r", 'i') => Some(vec![("り", Stop)]),
-    ("r", 'u') => Some(vec![("る", Stop)]),
-    ("r", 'e') => Some(vec![("れ", Stop)]),
-    ("r", 'o') => Some(vec![("ろ", Stop)]),
-    ("r", 'y') => Some(vec![("ry", Continue)]),
-    ("ry", 'a') => Some(vec![("り", Stop), ("ゃ", Stop)]),
-    ("ry", 'i') => Some(vec![("り", Stop), ("ぃ", Stop)]),
-    ("ry", 'u') => Some(vec![("り", Stop), ("ゅ", Stop)]),
-    ("ry", 'e') => Some(vec![("り", Stop), ("ぇ", Stop)]),
-    ("ry", 'o') => Some(vec![("り", Stop), ("ょ", Stop)]),
-
-    ("", 'w') => Some(vec![("w", Continue)]),
-    ("w", 'a') => Some(vec![("わ", Stop)]),
-    ("w", 'i') => Some(vec![("う", Stop), ("ぃ", Stop)]),
-    ("w", 'u') => Some(vec![("う", Stop)]),
-    ("w", 'e') => Some(vec![("う", Stop), ("ぇ", Stop)]),
-    ("w", 'o') => Some(vec![("を", Stop)]),
-
-    ("", 'v') => Some(vec![("v", Continue)]),
-    ("v", 'a') => Some(vec![("う", Stop), ("゛", Stop), ("ぁ", Stop)]),
-    ("v", 'i') => Some(vec![("う", Stop), ("゛", Stop), ("ぃ", Stop)]),
-    ("v", 'u') => Some(vec![("う", Stop), ("゛", Stop)]),
-    ("v", 'e') => Some(vec![("う", Stop), ("゛", Stop), ("ぇ", Stop)]),
-    ("v", 'o') => Some(vec![("ぼ", Stop)]),
-
-    ("", 'x') => Some(vec![("x", Continue)]),
-    ("x", 'a') => Some(vec![("ぁ", Stop)]),
-    ("x", 'i') => Some(vec![("ぃ", Stop)]),
-    ("x", 'u') => Some(vec![("ぅ", Stop)]),
-    ("x", 'e') => Some(vec![("ぇ", Stop)]),
-    ("x", 'o') => Some(vec![("ぉ", Stop)]),
-
-    ("x", 'y') => Some(vec![("xy", Continue)]),
-    ("xy", 'a') => Some(vec![("ゃ", Stop)]),
-    ("xy", 'i') => Some(vec![("い", Stop)]),
-    ("xy", 'u') => Some(vec![("ゅ", Stop)]),
-    ("xy", 'e') => Some(vec![("え", Stop)]),
-    ("xy", 'o') => Some(vec![("ょ", Stop)]),
-
-    // 「tte」 -> 「って」のような促音のルール
-    // TODO: 「kkkk」 -> 「っっっk」のような連続した促音のルールが未実装
-    ("w", 'w') => Some(vec![("っ", Stop), ("w", Continue)]),
-    ("r", 'r') => Some(vec![("っ", Stop), ("r", Continue)]),
-    ("t", 't') => Some(vec![("っ", Stop), ("t", Continue)]),
-    ("y", 'y') => Some(vec![("っ", Stop), ("y", Continue)]),
-    ("p", 'p') => Some(vec![("っ", Stop), ("p", Continue)]),
-    ("s", 's') => Some(vec![("っ", Stop), ("s", Continue)]),
-    ("d", 'd') => Some(vec![("っ", Stop), ("d", Continue)]),
-    ("g", 'g') => Some(vec![("っ", Stop), ("g", Continue)]),
-    ("h", 'h') => Some(vec![("っ", Stop), ("h", Continue)]),
-    ("j", 'j') => Some(vec![("っ", Stop), ("j", Continue)]),
-    ("k", 'k') => Some(vec![("っ", Stop), ("k", Continue)]),
-    ("l", 'l') => Some(vec![("っ", Stop), ("l", Continue)]),
-    ("z", 'z') => Some(vec![("っ", Stop), ("z", Continue)]),
-    ("x", 'x') => Some(vec![("っ", Stop), ("x", Continue)]),
-    ("c", 'c') => Some(vec![("っ", Stop), ("c", Continue)]),
-    ("v", 'v') => Some(vec![("っ", Stop), ("v", Continue)]),
-    ("b", 'b') => Some(vec![("っ", Stop), ("b", Continue)]),
-    ("m", 'm') => Some(vec![("っ", Stop), ("m", Continue)]),
-
-    // 「kanji」を「かんじ」にするように、「n」での「ん」の入力ルール
-    ("n", 'w') => Some(vec![("ん", Stop), ("w", Continue)]),
-    ("n", 'r') => Some(vec![("ん", Stop), ("r", Continue)]),
-    ("n", 't') => Some(vec![("ん", Stop), ("t", Continue)]),
-    ("n", 'p') => Some(vec![("ん", Stop), ("p", Continue)]),
-    ("n", 's') => Some(vec![("ん", Stop), ("s", Continue)]),
-    ("n", 'd') => Some(vec![("ん", Stop), ("d", Continue)]),
-    ("n", 'g') => Some(vec![("ん", Stop), ("g", Continue)]),
-    ("n", 'h') => Some(vec![("ん", Stop), ("h", Continue)]),
-    ("n", 'j') => Some(vec![("ん", Stop), ("j", Continue)]),
-    ("n", 'k') => Some(vec![("ん", Stop), ("k", Continue)]),
-    ("n", 'l') => Some(vec![("ん", Stop), ("l", Continue)]),
-    ("n", 'z') => Some(vec![("ん", Stop), ("z", Continue)]),
-    ("n", 'x') => Some(vec![("ん", Stop), ("x", Continue)]),
-    ("n", 'c') => Some(vec![("ん", Stop), ("c", Continue)]),
-    ("n", 'v') => Some(vec![("ん", Stop), ("v", Continue)]),
-    ("n", 'b') => Some(vec![("ん", Stop), ("b", Continue)]),
-    ("n", 'm') => Some(vec![("ん", Stop), ("m", Continue)]),
-
-    // 数字類
-    ("", '1') => Some(vec![("1", Stop)]),
-    ("", '2') => Some(vec![("2", Stop)]),
-    ("", '3') => Some(vec![("3", Stop)]),
-    ("", '4') => Some(vec![("4", Stop)]),
-    ("", '5') => Some(vec![("5", Stop)]),
-    ("", '6') => Some(vec![("6", Stop)]),
-    ("", '7') => Some(vec![("7", Stop)]),
-    ("", '8') => Some(vec![("8", Stop)]),
-    ("", '9') => Some(vec![("9", Stop)]),
-    ("", '0') => Some(vec![("0", Stop)]),
-
-    // 記号類
-    ("", ',') => Some(vec![("、", Stop)]),
-    ("", '.') => Some(vec![("。", Stop)]),
-    ("", '?') => Some(vec![("？", Stop)]),
-    ("", '/') => Some(vec![("/", Stop)]),
-    ("", ';') => Some(vec![(";", Stop)]),
-    ("", ':') => Some(vec![(":", Stop)]),
-    ("", '\'') => Some(vec![("\\", Stop)]),
-    ("", '`') => Some(vec![("`", Stop)]),
-    ("", '~') => Some(vec![("~", Stop)]),
-
-    ("", '!') => Some(vec![("！", Stop)]),
-    ("", '@') => Some(vec![("@", Stop)]),
-    ("", '#') => Some(vec![("#", Stop)]),
-    ("", '$') => Some(vec![("$", Stop)]),
-    ("", '%') => Some(vec![("%", Stop)]),
-    ("", '^') => Some(vec![("^", Stop)]),
-    ("", '&') => Some(vec![("&", Stop)]),
-    ("", '*') => Some(vec![("*", Stop)]),
-
-    ("", '-') => Some(vec![("ー", Stop)]),
-    ("", '_') => Some(vec![("_", Stop)]),
-    ("", '+') => Some(vec![("+", Stop)]),
-    ("", '=') => Some(vec![("=", Stop)]),
-
-    ("", '\\') => Some(vec![("\\", Stop)]),
-    ("", '|') => Some(vec![("|", Stop)]),
-
-    ("", '(') => Some(vec![("(", Stop)]),
-    ("", ')') => Some(vec![(")", Stop)]),
-    ("", '[') => Some(vec![("「", Stop)]),
-    ("", ']') => Some(vec![("」", Stop)]),
-    ("", '{') => Some(vec![("{", Stop)]),
-    ("", '}') => Some(vec![("}", Stop)]),
-    ("", '<') => Some(vec![("<", Stop)]),
-    ("", '>') => Some(vec![(">", Stop)]),
-
-    // 複合記号類
-    ("z", ' ') => Some(vec![("　", Stop)]),
-    ("z", '-') => Some(vec![("〜", Stop)]),
-
-    ("z", '[') => Some(vec![("『", Stop)]),
-    ("z", ']') => Some(vec![("』", Stop)]),
-
-    ("z", ',') => Some(vec![("‥", Stop)]),
-    ("z", '.') => Some(vec![("…", Stop)]),
-    ("z", '/') => Some(vec![("・", Stop)]),
-
-    ("z", 'h') => Some(vec![("←", Stop)]),
-    ("z", 'j') => Some(vec![("↓", Stop)]),
-    ("z", 'k') => Some(vec![("↑", Stop)]),
-    ("z", 'l') => Some(vec![("→", Stop)]),
-
-    _ => None,
-  }?;
-
-  Some(
-    pairs
-      .into_iter()
-      .map(|(c, state)| BufferPair::new(Hiragana, c, state))
-      .collect::<Vec<BufferPair>>(),
-  )
-}
+// ローマ字からひらがなへの変換ルール
+// (入力, 出力, 次の入力に残す文字列) の組で、カタカナはここから変換して使う
+pub const RULES: &[(&str, &str, &str)] = &[
+  ("a", "あ", ""),
+  ("i", "い", ""),
+  ("u", "う", ""),
+  ("e", "え", ""),
+  ("o", "お", ""),
+  ("ka", "か", ""),
+  ("ki", "き", ""),
+  ("ku", "く", ""),
+  ("ke", "け", ""),
+  ("ko", "こ", ""),
+  ("kya", "きゃ", ""),
+  ("kyi", "きぃ", ""),
+  ("kyu", "きゅ", ""),
+  ("kye", "きぇ", ""),
+  ("kyo", "きょ", ""),
+  ("ga", "が", ""),
+  ("gi", "ぎ", ""),
+  ("gu", "ぐ", ""),
+  ("ge", "げ", ""),
+  ("go", "ご", ""),
+  ("gya", "ぎゃ", ""),
+  ("gyi", "ぎぃ", ""),
+  ("gyu", "ぎゅ", ""),
+  ("gye", "ぎぇ", ""),
+  ("gyo", "ぎょ", ""),
+  ("sa", "さ", ""),
+  ("si", "し", ""),
+  ("su", "す", ""),
+  ("se", "せ", ""),
+  ("so", "そ", ""),
+  ("sya", "しゃ", ""),
+  ("syi", "しぃ", ""),
+  ("syu", "しゅ", ""),
+  ("sye", "しぇ", ""),
+  ("syo", "しょ", ""),
+  ("sha", "しゃ", ""),
+  ("shi", "し", ""),
+  ("shu", "しゅ", ""),
+  ("she", "しぇ", ""),
+  ("sho", "しょ", ""),
+  ("za", "ざ", ""),
+  ("zi", "じ", ""),
+  ("zu", "ず", ""),
+  ("ze", "ぜ", ""),
+  ("zo", "ぞ", ""),
+  ("zya", "じゃ", ""),
+  ("zyi", "じぃ", ""),
+  ("zyu", "じゅ", ""),
+  ("zye", "じぇ", ""),
+  ("zyo", "じょ", ""),
+  ("ta", "た", ""),
+  ("ti", "ち", ""),
+  ("tu", "つ", ""),
+  ("te", "て", ""),
+  ("to", "と", ""),
+  ("tya", "ちゃ", ""),
+  ("tyi", "ちぃ", ""),
+  ("tyu", "ちゅ", ""),
+  ("tye", "ちぇ", ""),
+  ("tyo", "ちょ", ""),
+  ("tha", "てぁ", ""),
+  ("thi", "てぃ", ""),
+  ("thu", "てゅ", ""),
+  ("the", "てぇ", ""),
+  ("tho", "てょ", ""),
+  ("tsa", "つぁ", ""),
+  ("tsi", "つぃ", ""),
+  ("tsu", "つ", ""),
+  ("tse", "つぇ", ""),
+  ("tso", "つぉ", ""),
+  ("da", "だ", ""),
+  ("di", "ぢ", ""),
+  ("du", "づ", ""),
+  ("de", "で", ""),
+  ("do", "ど", ""),
+  ("dha", "でゃ", ""),
+  ("dhi", "でぃ", ""),
+  ("dhu", "でゅ", ""),
+  ("dhe", "でぇ", ""),
+  ("dho", "でょ", ""),
+  ("dya", "ぢゃ", ""),
+  ("dyi", "ぢぃ", ""),
+  ("dyu", "ぢゅ", ""),
+  ("dye", "ぢぇ", ""),
+  ("dyo", "ぢょ", ""),
+  ("cha", "ちゃ", ""),
+  ("chi", "ち", ""),
+  ("chu", "ちゅ", ""),
+  ("che", "ちぇ", ""),
+  ("cho", "ちょ", ""),
+  ("cya", "ちゃ", ""),
+  ("cyi", "ちぃ", ""),
+  ("cyu", "ちゅ", ""),
+  ("cye", "ちぇ", ""),
+  ("cyo", "ちょ", ""),
+  ("na", "な", ""),
+  ("ni", "に", ""),
+  ("nu", "ぬ", ""),
+  ("ne", "ね", ""),
+  ("no", "の", ""),
+  ("n", "ん", ""),
+  ("nn", "ん", ""),
+  ("n'", "ん", ""),
+  ("nya", "にゃ", ""),
+  ("nyi", "にぃ", ""),
+  ("nyu", "にゅ", ""),
+  ("nye", "にぇ", ""),
+  ("nyo", "にょ", ""),
+  ("ha", "は", ""),
+  ("hi", "ひ", ""),
+  ("hu", "ふ", ""),
+  ("he", "へ", ""),
+  ("ho", "ほ", ""),
+  ("hya", "ひゃ", ""),
+  ("hyi", "ひぃ", ""),
+  ("hyu", "ひゅ", ""),
+  ("hye", "ひぇ", ""),
+  ("hyo", "ひょ", ""),
+  ("pa", "ぱ", ""),
+  ("pi", "ぴ", ""),
+  ("pu", "ぷ", ""),
+  ("pe", "ぺ", ""),
+  ("po", "ぽ", ""),
+  ("pya", "ぴゃ", ""),
+  ("pyi", "ぴぃ", ""),
+  ("pyu", "ぴゅ", ""),
+  ("pye", "ぴぇ", ""),
+  ("pyo", "ぴょ", ""),
+  ("ba", "ば", ""),
+  ("bi", "び", ""),
+  ("bu", "ぶ", ""),
+  ("be", "べ", ""),
+  ("bo", "ぼ", ""),
+  ("bya", "びゃ", ""),
+  ("byi", "びぃ", ""),
+  ("byu", "びゅ", ""),
+  ("bye", "びぇ", ""),
+  ("byo", "びょ", ""),
+  ("fa", "ふぁ", ""),
+  ("fi", "ふぃ", ""),
+  ("fu", "ふ", ""),
+  ("fe", "ふぇ", ""),
+  ("fo", "ふぉ", ""),
+  ("fya", "ふゃ", ""),
+  ("fyi", "ふぃ", ""),
+  ("fyu", "ふゅ", ""),
+  ("fye", "ふぇ", ""),
+  ("fyo", "ふょ", ""),
+  ("ma", "ま", ""),
+  ("mi", "み", ""),
+  ("mu", "む", ""),
+  ("me", "め", ""),
+  ("mo", "も", ""),
+  ("mya", "みゃ", ""),
+  ("myi", "みぃ", ""),
+  ("myu", "みゅ", ""),
+  ("mye", "みぇ", ""),
+  ("myo", "みょ", ""),
+  ("ya", "や", ""),
+  ("yi", "い", ""),
+  ("yu", "ゆ", ""),
+  ("ye", "いぇ", ""),
+  ("yo", "よ", ""),
+  ("ja", "じゃ", ""),
+  ("ji", "じ", ""),
+  ("ju", "じゅ", ""),
+  ("je", "じぇ", ""),
+  ("jo", "じょ", ""),
+  ("jya", "じゃ", ""),
+  ("jyi", "じぃ", ""),
+  ("jyu", "じゅ", ""),
+  ("jye", "じぇ", ""),
+  ("jyo", "じょ", ""),
+  ("ra", "ら", ""),
+  ("ri", "り", ""),
+  ("ru", "る", ""),
+  ("re", "れ", ""),
+  ("ro", "ろ", ""),
+  ("rya", "りゃ", ""),
+  ("ryi", "りぃ", ""),
+  ("ryu", "りゅ", ""),
+  ("rye", "りぇ", ""),
+  ("ryo", "りょ", ""),
+  ("wa", "わ", ""),
+  ("wi", "うぃ", ""),
+  ("wu", "う", ""),
+  ("we", "うぇ", ""),
+  ("wo", "を", ""),
+  ("va", "ゔぁ", ""),
+  ("vi", "ゔぃ", ""),
+  ("vu", "ゔ", ""),
+  ("ve", "ゔぇ", ""),
+  ("vo", "ゔぉ", ""),
+  ("xa", "ぁ", ""),
+  ("xi", "ぃ", ""),
+  ("xu", "ぅ", ""),
+  ("xe", "ぇ", ""),
+  ("xo", "ぉ", ""),
+  ("xya", "ゃ", ""),
+  ("xyi", "ぃ", ""),
+  ("xyu", "ゅ", ""),
+  ("xye", "ぇ", ""),
+  ("xyo", "ょ", ""),
+  // 数字類
+  ("1", "1", ""),
+  ("2", "2", ""),
+  ("3", "3", ""),
+  ("4", "4", ""),
+  ("5", "5", ""),
+  ("6", "6", ""),
+  ("7", "7", ""),
+  ("8", "8", ""),
+  ("9", "9", ""),
+  ("0", "0", ""),
+  // 記号類
+  (",", "、", ""),
+  (".", "。", ""),
+  ("?", "？", ""),
+  ("/", "/", ""),
+  (";", ";", ""),
+  (":", ":", ""),
+  ("'", "'", ""),
+  ("`", "`", ""),
+  ("~", "~", ""),
+  ("!", "！", ""),
+  ("@", "@", ""),
+  ("#", "#", ""),
+  ("$", "$", ""),
+  ("%", "%", ""),
+  ("^", "^", ""),
+  ("&", "&", ""),
+  ("*", "*", ""),
+  ("-", "ー", ""),
+  ("_", "_", ""),
+  ("+", "+", ""),
+  ("=", "=", ""),
+  ("\\", "\\", ""),
+  ("|", "|", ""),
+  ("(", "(", ""),
+  (")", ")", ""),
+  ("[", "「", ""),
+  ("]", "」", ""),
+  ("{", "{", ""),
+  ("}", "}", ""),
+  ("<", "<", ""),
+  (">", ">", ""),
+  // 複合記号類
+  ("z ", "　", ""),
+  ("z-", "〜", ""),
+  ("z[", "『", ""),
+  ("z]", "』", ""),
+  ("z,", "‥", ""),
+  ("z.", "…", ""),
+  ("z/", "・", ""),
+  ("zh", "←", ""),
+  ("zj", "↓", ""),
+  ("zk", "↑", ""),
+  ("zl", "→", ""),
+];
+
+// 「tte」 -> 「って」のような促音のルールを作る子音
+pub const SOKUON: &[char] = &[
+  'w', 'r', 't', 'y', 'p', 's', 'd', 'g', 'h', 'j', 'k', 'z', 'x', 'c', 'v', 'b', 'm', 'f',
+];
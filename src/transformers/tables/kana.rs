@@ -0,0 +1,214 @@
+// JISかな配列によるかな入力
+// USキーボードで打った文字を、JISキーボードの同じ位置のキーのかなに置き換える
+// シフトは小書きのかなに使い、濁点と半濁点は直前のかなに結合する
+use super::RuleTable;
+use std::collections::HashMap;
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputMethod {
+  Romaji,
+  Kana,
+  Nicola,
+}
+
+impl Default for InputMethod {
+  fn default() -> Self {
+    InputMethod::Romaji
+  }
+}
+
+pub const DAKUTEN: char = '゛';
+pub const HANDAKUTEN: char = '゜';
+
+// JISキーボードにしかない「ー」と「ろ」は「`」と「~」に置く
+pub const KEYMAP: &[(char, char)] = &[
+  ('1', 'ぬ'),
+  ('2', 'ふ'),
+  ('3', 'あ'),
+  ('4', 'う'),
+  ('5', 'え'),
+  ('6', 'お'),
+  ('7', 'や'),
+  ('8', 'ゆ'),
+  ('9', 'よ'),
+  ('0', 'わ'),
+  ('-', 'ほ'),
+  ('=', 'へ'),
+  ('q', 'た'),
+  ('w', 'て'),
+  ('e', 'い'),
+  ('r', 'す'),
+  ('t', 'か'),
+  ('y', 'ん'),
+  ('u', 'な'),
+  ('i', 'に'),
+  ('o', 'ら'),
+  ('p', 'せ'),
+  ('[', DAKUTEN),
+  (']', HANDAKUTEN),
+  ('\\', 'む'),
+  ('a', 'ち'),
+  ('s', 'と'),
+  ('d', 'し'),
+  ('f', 'は'),
+  ('g', 'き'),
+  ('h', 'く'),
+  ('j', 'ま'),
+  ('k', 'の'),
+  ('l', 'り'),
+  (';', 'れ'),
+  ('\'', 'け'),
+  ('z', 'つ'),
+  ('x', 'さ'),
+  ('c', 'そ'),
+  ('v', 'ひ'),
+  ('b', 'こ'),
+  ('n', 'み'),
+  ('m', 'も'),
+  (',', 'ね'),
+  ('.', 'る'),
+  ('/', 'め'),
+  ('`', 'ー'),
+  // シフト
+  ('#', 'ぁ'),
+  ('$', 'ぅ'),
+  ('%', 'ぇ'),
+  ('^', 'ぉ'),
+  ('&', 'ゃ'),
+  ('*', 'ゅ'),
+  ('(', 'ょ'),
+  (')', 'を'),
+  ('E', 'ぃ'),
+  ('Z', 'っ'),
+  ('<', '、'),
+  ('>', '。'),
+  ('?', '・'),
+  ('{', '「'),
+  ('}', '」'),
+  ('~', 'ろ'),
+];
+
+// 送り仮名の読みに使う子音
+const OKURI_CONSONANTS: &[(&str, char)] = &[
+  ("あ", 'a'),
+  ("い", 'i'),
+  ("う", 'u'),
+  ("え", 'e'),
+  ("お", 'o'),
+  ("かきくけこ", 'k'),
+  ("がぎぐげご", 'g'),
+  ("さしすせそ", 's'),
+  ("ざじずぜぞ", 'z'),
+  ("たちつてとっ", 't'),
+  ("だぢづでど", 'd'),
+  ("なにぬねの", 'n'),
+  ("はひふへほ", 'h'),
+  ("ばびぶべぼ", 'b'),
+  ("ぱぴぷぺぽ", 'p'),
+  ("まみむめも", 'm'),
+  ("やゆよゃゅょ", 'y'),
+  ("らりるれろ", 'r'),
+  ("わを", 'w'),
+  ("ん", 'n'),
+];
+
+pub fn keymap() -> HashMap<char, char> {
+  KEYMAP.iter().cloned().collect()
+}
+
+// かなごとのルール。濁点と半濁点が付くかなは、続くキーを待つために入力途中として扱う
+pub fn rule_table() -> RuleTable {
+  let mut ret = RuleTable::new();
+  KEYMAP.iter().for_each(|(_, kana)| {
+    let kana = kana.to_string();
+    ret.insert(&kana, &kana, "");
+  });
+  KEYMAP.iter().for_each(|(_, kana)| {
+    if let Some(voiced) = voiced(*kana) {
+      ret.insert(&format!("{}{}", kana, DAKUTEN), &voiced.to_string(), "");
+    }
+    if let Some(semi_voiced) = semi_voiced(*kana) {
+      ret.insert(
+        &format!("{}{}", kana, HANDAKUTEN),
+        &semi_voiced.to_string(),
+        "",
+      );
+    }
+  });
+  ret.set_keymap(keymap());
+
+  ret
+}
+
+fn voiced(kana: char) -> Option<char> {
+  match kana {
+    'う' => Some('ゔ'),
+    _ if "かきくけこさしすせそたちつてとはひふへほ".contains(kana) => {
+      std::char::from_u32(kana as u32 + 1)
+    }
+    _ => None,
+  }
+}
+
+fn semi_voiced(kana: char) -> Option<char> {
+  match "はひふへほ".contains(kana) {
+    true => std::char::from_u32(kana as u32 + 2),
+    false => None,
+  }
+}
+
+// 送り仮名の先頭のかなから、辞書の読みに使う子音を求める
+pub fn okuri_consonant(kana: char) -> Option<char> {
+  let hiragana = match kana {
+    '\u{30A1}'..='\u{30F6}' => std::char::from_u32(kana as u32 - 0x60)?,
+    _ => kana,
+  };
+
+  OKURI_CONSONANTS
+    .iter()
+    .find(|(kana, _)| kana.contains(hiragana))
+    .map(|(_, consonant)| *consonant)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::super::BufferState::*;
+
+  #[test]
+  fn rule_table() {
+    let table = super::rule_table();
+    let convert = |current: &str, character| {
+      table.convert(current, character).map(|pairs| {
+        pairs
+          .into_iter()
+          .map(|(buffer, state)| (buffer, state == Stop))
+          .collect::<Vec<_>>()
+      })
+    };
+
+    assert_eq!(convert("", 't'), Some(vec![("か".to_owned(), false)]));
+    assert_eq!(convert("か", '['), Some(vec![("が".to_owned(), true)]));
+    assert_eq!(convert("ほ", ']'), Some(vec![("ぽ".to_owned(), true)]));
+    assert_eq!(convert("う", '['), Some(vec![("ゔ".to_owned(), true)]));
+    assert_eq!(
+      convert("か", 'q'),
+      Some(vec![("か".to_owned(), true), ("た".to_owned(), false)])
+    );
+    assert_eq!(convert("", 'n'), Some(vec![("み".to_owned(), true)]));
+    assert_eq!(convert("", 'E'), Some(vec![("ぃ".to_owned(), true)]));
+    assert_eq!(convert("", '#'), Some(vec![("ぁ".to_owned(), true)]));
+    assert_eq!(convert("", '['), Some(vec![("゛".to_owned(), true)]));
+    assert!(table.is_mapped('E'));
+    assert!(!table.is_mapped('Q'));
+  }
+
+  #[test]
+  fn okuri_consonant() {
+    assert_eq!(super::okuri_consonant('く'), Some('k'));
+    assert_eq!(super::okuri_consonant('ぐ'), Some('g'));
+    assert_eq!(super::okuri_consonant('ッ'), Some('t'));
+    assert_eq!(super::okuri_consonant('い'), Some('i'));
+    assert_eq!(super::okuri_consonant('、'), None);
+  }
+}
@@ -1,13 +1,29 @@
+mod act;
+mod azik;
 mod buffer_pair;
 mod buffer_pairs;
 mod direct;
+mod em_eisu;
+mod en_katakana;
+mod extended_romaji;
 mod hiragana;
+mod kana;
 mod katakana;
+mod nicola;
+mod punctuation;
+mod romaji_rule;
+mod rule_table;
 
 use super::BufferState;
 
 pub use buffer_pair::BufferPair;
 pub use buffer_pairs::BufferPairs;
+pub use en_katakana::from_kana as to_en_katakana;
+pub use kana::{okuri_consonant, InputMethod};
+pub use nicola::{is_character_key as is_nicola_key, lookup as nicola_lookup};
+pub use punctuation::PunctuationStyle;
+pub use romaji_rule::RomajiRule;
+pub use rule_table::{RomajiTable, RuleTable};
 
 #[derive(Eq, PartialEq, Clone, Copy, Hash, Debug)]
 pub enum LetterType {
@@ -0,0 +1,118 @@
+// NICOLA配列(親指シフト)
+// キーごとに単独打鍵、左親指との同時打鍵、右親指との同時打鍵のかなを並べる。割り当てがない場合は空文字
+use super::RuleTable;
+use crate::keyboards::Thumb;
+
+const LAYOUT: &[(char, &str, &str, &str)] = &[
+  ('1', "1", "？", ""),
+  ('2', "2", "／", ""),
+  ('3', "3", "～", ""),
+  ('4', "4", "「", ""),
+  ('5', "5", "」", ""),
+  ('6', "6", "", "［"),
+  ('7', "7", "", "］"),
+  ('8', "8", "", "（"),
+  ('9', "9", "", "）"),
+  ('0', "0", "", "｛"),
+  ('-', "-", "", "｝"),
+  ('q', "。", "ぁ", ""),
+  ('w', "か", "え", "が"),
+  ('e', "た", "り", "だ"),
+  ('r', "こ", "ゃ", "ご"),
+  ('t', "さ", "れ", "ざ"),
+  ('y', "ら", "ぱ", "よ"),
+  ('u', "ち", "ぢ", "に"),
+  ('i', "く", "ぐ", "る"),
+  ('o', "つ", "づ", "ま"),
+  ('p', "，", "ぴ", "ぇ"),
+  ('[', "、", "", ""),
+  ('a', "う", "を", "ゔ"),
+  ('s', "し", "あ", "じ"),
+  ('d', "て", "な", "で"),
+  ('f', "け", "ゅ", "げ"),
+  ('g', "せ", "も", "ぜ"),
+  ('h', "は", "ば", "み"),
+  ('j', "と", "ど", "お"),
+  ('k', "き", "ぎ", "の"),
+  ('l', "い", "ぽ", "ょ"),
+  (';', "ん", "", "っ"),
+  ('z', "．", "ぅ", ""),
+  ('x', "ひ", "ー", "び"),
+  ('c', "す", "ろ", "ず"),
+  ('v', "ふ", "や", "ぶ"),
+  ('b', "へ", "ぃ", "べ"),
+  ('n', "め", "ぷ", "ぬ"),
+  ('m', "そ", "ぞ", "ゆ"),
+  (',', "ね", "ぺ", "む"),
+  ('.', "ほ", "ぼ", "わ"),
+  ('/', "・", "ぉ", ""),
+];
+
+pub fn is_character_key(key: char) -> bool {
+  LAYOUT.iter().any(|(k, _, _, _)| *k == key)
+}
+
+pub fn lookup(key: char, thumb: Option<Thumb>) -> Option<&'static str> {
+  let (_, single, left, right) = LAYOUT.iter().find(|(k, _, _, _)| *k == key)?;
+  let ret = match thumb {
+    None => single,
+    Some(Thumb::Left) => left,
+    Some(Thumb::Right) => right,
+  };
+
+  match ret.is_empty() {
+    true => None,
+    false => Some(ret),
+  }
+}
+
+// 単独打鍵はキーの文字をかなに置き換え、同時打鍵はかなをそのまま入力する
+pub fn rule_table() -> RuleTable {
+  let mut ret = RuleTable::new();
+  LAYOUT.iter().for_each(|(_, single, left, right)| {
+    [single, left, right]
+      .iter()
+      .filter(|kana| !kana.is_empty())
+      .for_each(|kana| ret.insert(kana, kana, ""));
+  });
+  ret.set_keymap(
+    LAYOUT
+      .iter()
+      .filter_map(|(key, single, _, _)| Some((*key, single.chars().next()?)))
+      .collect(),
+  );
+
+  ret
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn lookup() {
+    assert_eq!(super::lookup('w', None), Some("か"));
+    assert_eq!(super::lookup('w', Some(Thumb::Left)), Some("え"));
+    assert_eq!(super::lookup('w', Some(Thumb::Right)), Some("が"));
+    assert_eq!(super::lookup('h', Some(Thumb::Right)), Some("み"));
+    assert_eq!(super::lookup('q', Some(Thumb::Right)), None);
+    assert_eq!(super::lookup('@', None), None);
+    assert!(is_character_key('/'));
+    assert!(!is_character_key('@'));
+  }
+
+  #[test]
+  fn rule_table() {
+    let table = super::rule_table();
+    let output = |current: &str, c| {
+      table
+        .convert(current, c)
+        .map(|pairs| pairs.into_iter().map(|(s, _)| s).collect::<String>())
+    };
+    assert_eq!(output("", 'w'), Some("か".to_owned()));
+    assert_eq!(output("", 'が'), Some("が".to_owned()));
+    assert_eq!(output("", ';'), Some("ん".to_owned()));
+    assert!(table.is_mapped('k'));
+    assert!(!table.is_mapped('K'));
+  }
+}
@@ -0,0 +1,59 @@
+use std::convert::TryFrom;
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PunctuationStyle {
+  // 、。「」ー
+  Japanese,
+  // ，．「」ー
+  Western,
+  // ,.[]-
+  Ascii,
+}
+
+impl Default for PunctuationStyle {
+  fn default() -> Self {
+    PunctuationStyle::Japanese
+  }
+}
+
+impl TryFrom<u8> for PunctuationStyle {
+  type Error = &'static str;
+
+  fn try_from(style: u8) -> Result<Self, Self::Error> {
+    match style {
+      0 => Ok(PunctuationStyle::Japanese),
+      1 => Ok(PunctuationStyle::Western),
+      2 => Ok(PunctuationStyle::Ascii),
+      _ => Err("unknown punctuation style"),
+    }
+  }
+}
+
+impl PunctuationStyle {
+  pub fn rules(&self) -> &'static [(&'static str, &'static str, &'static str)] {
+    match self {
+      PunctuationStyle::Japanese => &[
+        (",", "、", ""),
+        (".", "。", ""),
+        ("[", "「", ""),
+        ("]", "」", ""),
+        ("-", "ー", ""),
+      ],
+      PunctuationStyle::Western => &[
+        (",", "，", ""),
+        (".", "．", ""),
+        ("[", "「", ""),
+        ("]", "」", ""),
+        ("-", "ー", ""),
+      ],
+      PunctuationStyle::Ascii => &[
+        (",", ",", ""),
+        (".", ".", ""),
+        ("[", "[", ""),
+        ("]", "]", ""),
+        ("-", "-", ""),
+      ],
+    }
+  }
+}
@@ -0,0 +1,265 @@
+// 設定で既定のローマ字かな変換ルールを追加・上書き・削除するためのもの
+// outputを省略するとinputに一致するルールを削除する
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct RomajiRule {
+  pub input: String,
+  #[serde(default)]
+  pub output: Option<String>,
+  #[serde(default)]
+  pub next: String,
+}
+
+#[derive(Eq, PartialEq, Clone, Debug)]
+enum Token {
+  Open,
+  Close,
+  Dot,
+  Nil,
+  Symbol,
+  Str(String),
+}
+
+// 行番号はエラーの表示に使う
+#[derive(Eq, PartialEq, Clone, Debug)]
+enum Expr {
+  Dot,
+  Nil,
+  Symbol,
+  Str(String),
+  List(usize, Vec<Expr>),
+}
+
+impl RomajiRule {
+  pub fn new<S: Into<String>>(input: S, output: Option<S>, next: S) -> Self {
+    RomajiRule {
+      input: input.into(),
+      output: output.map(|s| s.into()),
+      next: next.into(),
+    }
+  }
+
+  // skk-rom-kana-rule-listと同じ形式のルールを読む
+  //   ("tt" "t" "っ")               入力、次の入力に残す文字列、出力
+  //   ("z," nil "‥")
+  //   ("a" nil ("ア" . "あ"))       カタカナとひらがなの組はひらがなを使う
+  //   ("la" nil nil)                既定のルールを削除する
+  // '(( … ) ( … ))のようなリストやsetqの中に書いたルールも読む
+  // 「;」から行末まではコメント
+  pub fn parse_rule_list(string: &str) -> Result<Vec<Self>, String> {
+    let error = |line: usize| {
+      format!(
+        "line {}: {}",
+        line,
+        string.lines().nth(line - 1).unwrap_or("")
+      )
+    };
+    let tokens = tokenize(string).map_err(error)?;
+    let exprs = read(&mut tokens.into_iter(), None).map_err(error)?;
+
+    let mut rules = vec![];
+    collect_rules(&exprs, &mut rules).map_err(error)?;
+
+    Ok(rules)
+  }
+}
+
+// 文字列で始まるリストをルールとして読み、それ以外のリストは中を探す
+fn collect_rules(exprs: &[Expr], rules: &mut Vec<RomajiRule>) -> Result<(), usize> {
+  exprs.iter().try_for_each(|expr| match expr {
+    Expr::List(line, items) => match items.first() {
+      Some(Expr::Str(_)) => {
+        rules.push(parse_rule(items).ok_or(*line)?);
+        Ok(())
+      }
+      _ => collect_rules(items, rules),
+    },
+    _ => Ok(()),
+  })
+}
+
+fn parse_rule(items: &[Expr]) -> Option<RomajiRule> {
+  use Expr::*;
+
+  let (input, next, output) = match items {
+    [Str(input), next, output] => (input, next, output),
+    _ => return None,
+  };
+  let next = match next {
+    Nil => "".to_owned(),
+    Str(next) => next.clone(),
+    _ => return None,
+  };
+  let output = match output {
+    Nil => None,
+    Str(output) => Some(output.clone()),
+    List(_, pair) => match &pair[..] {
+      [Str(_), Dot, Str(hiragana)] => Some(hiragana.clone()),
+      _ => return None,
+    },
+    _ => return None,
+  };
+
+  Some(RomajiRule::new(input.clone(), output, next))
+}
+
+// openには読んでいるリストの開き括弧の行を渡す
+fn read<I: Iterator<Item = (usize, Token)>>(
+  tokens: &mut I,
+  open: Option<usize>,
+) -> Result<Vec<Expr>, usize> {
+  let mut ret = vec![];
+  while let Some((line, token)) = tokens.next() {
+    ret.push(match token {
+      Token::Open => Expr::List(line, read(tokens, Some(line))?),
+      Token::Close => return open.map(|_| ret).ok_or(line),
+      Token::Dot => Expr::Dot,
+      Token::Nil => Expr::Nil,
+      Token::Symbol => Expr::Symbol,
+      Token::Str(string) => Expr::Str(string),
+    });
+  }
+
+  match open {
+    Some(line) => Err(line),
+    None => Ok(ret),
+  }
+}
+
+fn tokenize(string: &str) -> Result<Vec<(usize, Token)>, usize> {
+  let mut ret = vec![];
+  let mut line = 1;
+  let mut chars = string.chars().peekable();
+  while let Some(c) = chars.next() {
+    match c {
+      '\n' => line += 1,
+      ';' => while chars.next_if(|c| *c != '\n').is_some() {},
+      '(' => ret.push((line, Token::Open)),
+      ')' => ret.push((line, Token::Close)),
+      // 'や`はリストをそのまま書くためのものなので読み飛ばす
+      '\'' | '`' => {}
+      '"' => {
+        let start = line;
+        let mut string = String::new();
+        loop {
+          match chars.next().ok_or(start)? {
+            '"' => break,
+            '\\' => string.push(chars.next().ok_or(start)?),
+            c => {
+              if c == '\n' {
+                line += 1;
+              }
+              string.push(c)
+            }
+          }
+        }
+        ret.push((start, Token::Str(string)));
+      }
+      c if c.is_whitespace() => {}
+      c => {
+        let mut symbol = c.to_string();
+        while let Some(c) = chars.peek() {
+          if c.is_whitespace() || *c == '(' || *c == ')' || *c == ';' {
+            break;
+          }
+          symbol.push(*c);
+          chars.next();
+        }
+        ret.push((
+          line,
+          match &symbol as &str {
+            "." => Token::Dot,
+            "nil" => Token::Nil,
+            _ => Token::Symbol,
+          },
+        ));
+      }
+    }
+  }
+
+  Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_rule_list() {
+    let rules = RomajiRule::parse_rule_list(
+      r#"
+;; comment
+("tt" "t" "っ")
+("z," nil "‥") ; 二点リーダー
+("a" nil ("ア" . "あ"))
+("la" nil nil)
+("\"" nil "”")
+"#,
+    );
+    assert_eq!(
+      rules,
+      Ok(vec![
+        RomajiRule::new("tt", Some("っ"), "t"),
+        RomajiRule::new("z,", Some("‥"), ""),
+        RomajiRule::new("a", Some("あ"), ""),
+        RomajiRule::new("la", None, ""),
+        RomajiRule::new("\"", Some("”"), ""),
+      ])
+    );
+
+    assert_eq!(
+      RomajiRule::parse_rule_list("(\"a\" nil \"あ\")\n(\"b\" foo \"c\")"),
+      Err("line 2: (\"b\" foo \"c\")".to_owned())
+    );
+    assert!(RomajiRule::parse_rule_list("(\"a\" nil").is_err());
+    assert!(RomajiRule::parse_rule_list("(\"a").is_err());
+  }
+
+  #[test]
+  fn parse_elisp_rule_list() {
+    let rules = RomajiRule::parse_rule_list(
+      r#"
+(setq skk-rom-kana-rule-list
+      (append skk-rom-kana-rule-list
+              '(("hh" "h" ("ッ" . "っ"))
+                ;; 記号
+                ("z." nil "…") ("z/" nil "・")
+                ("la" nil
+                 nil))))
+"#,
+    );
+    assert_eq!(
+      rules,
+      Ok(vec![
+        RomajiRule::new("hh", Some("っ"), "h"),
+        RomajiRule::new("z.", Some("…"), ""),
+        RomajiRule::new("z/", Some("・"), ""),
+        RomajiRule::new("la", None, ""),
+      ])
+    );
+
+    assert_eq!(
+      RomajiRule::parse_rule_list("'((\"a\" nil \"あ\")\n  (\"b\" nil))"),
+      Err("line 2:   (\"b\" nil))".to_owned())
+    );
+    assert_eq!(
+      RomajiRule::parse_rule_list("'((\"a\" nil \"あ\")\n"),
+      Err("line 1: '((\"a\" nil \"あ\")".to_owned())
+    );
+  }
+
+  #[test]
+  fn deserialize() {
+    let rules: Vec<RomajiRule> = serde_json::from_str(
+      r#"[{"input": "z,", "output": "‥"}, {"input": "tt", "output": "っ", "next": "t"}, {"input": "la"}]"#,
+    )
+    .unwrap();
+    assert_eq!(
+      rules,
+      vec![
+        RomajiRule::new("z,", Some("‥"), ""),
+        RomajiRule::new("tt", Some("っ"), "t"),
+        RomajiRule::new("la", None, ""),
+      ]
+    );
+  }
+}
@@ -0,0 +1,402 @@
+use super::{act, azik, hiragana, kana, nicola};
+use super::{BufferState, InputMethod, PunctuationStyle, RomajiRule};
+use crate::keyboards::{KeyCode, KeyCombination, KeyCombinations};
+use crate::{combo, combos, RSKKConfig};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Rule {
+  pub output: String,
+  pub next: String,
+}
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RomajiTable {
+  Standard,
+  Azik,
+  Act,
+}
+
+impl Default for RomajiTable {
+  fn default() -> Self {
+    RomajiTable::Standard
+  }
+}
+
+impl RomajiTable {
+  // 「q」をルールに使う配列では、かなの切り替えを別のキーで行う
+  pub fn toggle_kana_keys(&self) -> KeyCombinations {
+    match self {
+      RomajiTable::Standard => combos![],
+      RomajiTable::Azik | RomajiTable::Act => combos![combo![KeyCode::from("[")]],
+    }
+  }
+
+  pub fn toggle_kana_character(&self) -> char {
+    match self {
+      RomajiTable::Standard => 'q',
+      RomajiTable::Azik | RomajiTable::Act => '[',
+    }
+  }
+}
+
+#[derive(Clone, Debug, Default)]
+struct Node {
+  rule: Option<Rule>,
+  children: HashMap<char, Node>,
+}
+
+// 入力列をキーにしたトライ木
+// 入力途中の列はBufferState::Continueとして保持し、ルールに到達したらStopにする
+// keymapがあるときは、入力した文字をkeymapで置き換えてからルールを引く
+#[derive(Clone, Debug)]
+pub struct RuleTable {
+  root: Node,
+  keymap: HashMap<char, char>,
+}
+
+// スレッドをまたいで同じ表を使う
+static DEFAULT_RULES: OnceLock<Arc<RuleTable>> = OnceLock::new();
+
+impl RuleTable {
+  pub fn new() -> Self {
+    RuleTable {
+      root: Node::default(),
+      keymap: HashMap::new(),
+    }
+  }
+
+  pub fn default_rules() -> Arc<RuleTable> {
+    DEFAULT_RULES
+      .get_or_init(|| Arc::new(RuleTable::from_rules(hiragana::RULES, hiragana::SOKUON)))
+      .clone()
+  }
+
+  pub fn from_rules(rules: &[(&str, &str, &str)], sokuon: &[char]) -> Self {
+    let mut ret = Self::new();
+    rules
+      .iter()
+      .for_each(|(input, output, next)| ret.insert(input, output, next));
+    sokuon
+      .iter()
+      .for_each(|c| ret.insert(&format!("{}{}", c, c), "っ", &c.to_string()));
+
+    ret
+  }
+
+  pub fn from_config(config: &RSKKConfig) -> Arc<RuleTable> {
+    let kana_table = match config.input_method {
+      InputMethod::Romaji => None,
+      InputMethod::Kana => Some(kana::rule_table()),
+      InputMethod::Nicola => Some(nicola::rule_table()),
+    };
+    if let Some(mut ret) = kana_table {
+      ret.apply(&config.romaji_rules);
+
+      return Arc::new(ret);
+    }
+
+    let layout = match config.romaji_table {
+      RomajiTable::Standard => None,
+      RomajiTable::Azik => Some(azik::LAYOUT),
+      RomajiTable::Act => Some(act::LAYOUT),
+    };
+    if layout.is_none()
+      && config.romaji_rules.is_empty()
+      && config.punctuation_style == PunctuationStyle::default()
+    {
+      return Self::default_rules();
+    }
+
+    let mut ret = (*Self::default_rules()).clone();
+    if let Some(layout) = layout {
+      layout.apply(&mut ret);
+    }
+    ret.apply_punctuation(config.punctuation_style);
+    ret.apply(&config.romaji_rules);
+
+    Arc::new(ret)
+  }
+
+  // 配列で別の用途に使われているキーは置き換えない
+  fn apply_punctuation(&mut self, style: PunctuationStyle) {
+    style.rules().iter().for_each(|(input, output, next)| {
+      if self.get(input).is_some() {
+        self.insert(input, output, next);
+      }
+    });
+  }
+
+  pub fn apply(&mut self, rules: &[RomajiRule]) {
+    rules.iter().for_each(|rule| match &rule.output {
+      Some(output) => self.insert(&rule.input, output, &rule.next),
+      None => self.remove(&rule.input),
+    });
+  }
+
+  pub fn set_keymap(&mut self, keymap: HashMap<char, char>) {
+    self.keymap = keymap;
+  }
+
+  pub fn is_kana_input(&self) -> bool {
+    !self.keymap.is_empty()
+  }
+
+  // かな入力で、シフトを押した文字も含めてかなが割り当てられているかどうか
+  pub fn is_mapped(&self, character: char) -> bool {
+    self.keymap.contains_key(&character)
+  }
+
+  pub fn insert(&mut self, input: &str, output: &str, next: &str) {
+    let node = input.chars().fold(&mut self.root, |node, c| {
+      node.children.entry(c).or_insert(Node::default())
+    });
+
+    node.rule = Some(Rule {
+      output: output.to_owned(),
+      next: next.to_owned(),
+    });
+  }
+
+  pub fn remove(&mut self, input: &str) {
+    Self::remove_from(&mut self.root, &input.chars().collect::<Vec<_>>());
+  }
+
+  pub fn get(&self, input: &str) -> Option<&Rule> {
+    self.node(input)?.rule.as_ref()
+  }
+
+  // 入力途中の列に続けてcharacterを入力したときにルールで扱うかどうか
+  // かな入力では、シフトを押した英字も▽の開始に使うため、キー設定に渡さない
+  pub fn claims(&self, pending: &str, character: char) -> bool {
+    self.continues(pending, character)
+      || self.node(&self.key(character).to_string()).is_some()
+      || self.is_mapped(character.to_ascii_lowercase())
+  }
+
+  // 入力途中の列がcharacterで続くかどうか。z/のように記号で続く場合に使う
+  pub fn continues(&self, pending: &str, character: char) -> bool {
+    let input = format!("{}{}", pending, self.key(character));
+    !pending.is_empty() && self.node(&input).is_some()
+  }
+
+  pub fn convert(&self, current: &str, character: char) -> Option<Vec<(String, BufferState)>> {
+    let character = self.key(character);
+    let input = current.to_owned() + &character.to_string();
+    match self.node(&input) {
+      Some(node) if !node.children.is_empty() => Some(vec![(input, BufferState::Continue)]),
+      Some(node) => node.rule.as_ref().map(Self::rule_to_pairs),
+      None if current.is_empty() && character.is_ascii() => None,
+      // 絵文字など、ルールにない文字を直接入力した場合はそのまま出力する
+      None if current.is_empty() => Some(vec![(input, BufferState::Stop)]),
+      None => {
+        // 「kanji」の「n」のように、続く文字で確定するルールはここで出力して先頭からやり直す
+        // 「kd」の「k」のようにルールのない列は、捨てずにそのまま出力する
+        // やり直せない文字でも、確定したかなは捨てない
+        let mut ret = match self.get(current) {
+          Some(rule) => Self::rule_to_pairs(rule),
+          None => vec![(current.to_owned(), BufferState::Stop)],
+        };
+        ret.append(&mut self.convert("", character).unwrap_or_default());
+
+        Some(ret)
+      }
+    }
+  }
+
+  // 「n」のように単独でもルールがある入力途中の列を、続く入力を待たずに出力する
+  pub fn flush(&self, current: &str) -> Option<Vec<(String, BufferState)>> {
+    match current.is_empty() {
+      true => None,
+      false => Some(Self::rule_to_pairs(self.get(current)?)),
+    }
+  }
+
+  // ローマ字では大文字を小文字として扱う
+  fn key(&self, character: char) -> char {
+    match self.keymap.get(&character) {
+      Some(mapped) => *mapped,
+      None if self.is_kana_input() => character,
+      None => character.to_lowercase().next().unwrap_or(character),
+    }
+  }
+
+  fn node(&self, input: &str) -> Option<&Node> {
+    input
+      .chars()
+      .try_fold(&self.root, |node, c| node.children.get(&c))
+  }
+
+  // 削除して空になった節は取り除き、入力途中として扱われないようにする
+  fn remove_from(node: &mut Node, input: &[char]) -> bool {
+    match input {
+      [] => node.rule = None,
+      [c, rest @ ..] => {
+        if let Some(child) = node.children.get_mut(c) {
+          if Self::remove_from(child, rest) {
+            node.children.remove(c);
+          }
+        }
+      }
+    }
+
+    node.rule.is_none() && node.children.is_empty()
+  }
+
+  fn rule_to_pairs(rule: &Rule) -> Vec<(String, BufferState)> {
+    let mut ret = rule
+      .output
+      .chars()
+      .map(|c| (c.to_string(), BufferState::Stop))
+      .collect::<Vec<_>>();
+    if !rule.next.is_empty() {
+      ret.push((rule.next.clone(), BufferState::Continue));
+    }
+
+    ret
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use BufferState::*;
+
+  fn pairs(items: &[(&str, BufferState)]) -> Option<Vec<(String, BufferState)>> {
+    Some(
+      items
+        .iter()
+        .map(|(s, state)| (s.to_string(), *state))
+        .collect(),
+    )
+  }
+
+  #[test]
+  fn convert() {
+    let table = RuleTable::default_rules();
+    assert_eq!(table.convert("", 'a'), pairs(&[("あ", Stop)]));
+    assert_eq!(table.convert("", 'k'), pairs(&[("k", Continue)]));
+    assert_eq!(table.convert("k", 'y'), pairs(&[("ky", Continue)]));
+    assert_eq!(
+      table.convert("ky", 'a'),
+      pairs(&[("き", Stop), ("ゃ", Stop)])
+    );
+    assert_eq!(
+      table.convert("t", 't'),
+      pairs(&[("っ", Stop), ("t", Continue)])
+    );
+    assert_eq!(
+      table.convert("n", 'k'),
+      pairs(&[("ん", Stop), ("k", Continue)])
+    );
+    assert_eq!(table.convert("n", 'a'), pairs(&[("な", Stop)]));
+    assert_eq!(table.convert("k", 'q'), pairs(&[("k", Stop)]));
+    assert_eq!(
+      table.convert("k", 'd'),
+      pairs(&[("k", Stop), ("d", Continue)])
+    );
+    assert_eq!(table.convert("", 'l'), None);
+    assert_eq!(table.convert("", 'é'), pairs(&[("é", Stop)]));
+    assert_eq!(
+      table.convert("n", '😀'),
+      pairs(&[("ん", Stop), ("😀", Stop)])
+    );
+  }
+
+  #[test]
+  fn from_config() {
+    let mut config = RSKKConfig::default_config();
+    config.romaji_rules = vec![
+      RomajiRule::new("z,", Some("，"), ""),
+      RomajiRule::new("tt", Some("っ"), ""),
+      RomajiRule::new("la", Some("ぁ"), ""),
+      RomajiRule::new("zh", None, ""),
+      RomajiRule::new("xya", None, ""),
+      RomajiRule::new("xyi", None, ""),
+      RomajiRule::new("xyu", None, ""),
+      RomajiRule::new("xye", None, ""),
+      RomajiRule::new("xyo", None, ""),
+    ];
+    let table = RuleTable::from_config(&config);
+
+    assert_eq!(table.convert("z", ','), pairs(&[("，", Stop)]));
+    assert_eq!(table.convert("t", 't'), pairs(&[("っ", Stop)]));
+    assert_eq!(table.convert("", 'l'), pairs(&[("l", Continue)]));
+    assert_eq!(table.convert("l", 'a'), pairs(&[("ぁ", Stop)]));
+    assert_eq!(
+      table.convert("z", 'h'),
+      pairs(&[("z", Stop), ("h", Continue)])
+    );
+    assert_eq!(
+      table.convert("x", 'y'),
+      pairs(&[("x", Stop), ("y", Continue)])
+    );
+    assert_eq!(table.convert("x", 'a'), pairs(&[("ぁ", Stop)]));
+
+    let table = RuleTable::from_config(&RSKKConfig::default_config());
+    assert!(Arc::ptr_eq(&table, &RuleTable::default_rules()));
+  }
+
+  #[test]
+  fn layouts() {
+    let mut config = RSKKConfig::default_config();
+    config.romaji_table = RomajiTable::Azik;
+    let table = RuleTable::from_config(&config);
+    let output = |input| table.get(input).map(|rule| rule.output.clone());
+    assert_eq!(output("kz"), Some("かん".to_owned()));
+    assert_eq!(output("kk"), Some("きん".to_owned()));
+    assert_eq!(output("kq"), Some("かい".to_owned()));
+    assert_eq!(output("kyp"), Some("きょう".to_owned()));
+    assert_eq!(output("q"), Some("ん".to_owned()));
+    assert_eq!(output(";"), Some("っ".to_owned()));
+    assert_eq!(output("xa"), Some("しゃ".to_owned()));
+    assert_eq!(output("ka"), Some("か".to_owned()));
+    assert_eq!(output("["), None);
+    assert!(table.claims("", 'q'));
+    assert!(!table.claims("", '['));
+
+    config.romaji_table = RomajiTable::Act;
+    config.romaji_rules = vec![RomajiRule::new("q", Some("ン"), "")];
+    let table = RuleTable::from_config(&config);
+    let output = |input| table.get(input).map(|rule| rule.output.clone());
+    assert_eq!(output("k;"), Some("かん".to_owned()));
+    assert_eq!(output("kn"), Some("きん".to_owned()));
+    assert_eq!(output("k'"), Some("かい".to_owned()));
+    assert_eq!(output("c,"), Some("こう".to_owned()));
+    assert_eq!(output("q"), Some("ン".to_owned()));
+  }
+
+  #[test]
+  fn punctuation_style() {
+    let mut config = RSKKConfig::default_config();
+    config.punctuation_style = PunctuationStyle::Western;
+    let table = RuleTable::from_config(&config);
+    assert_eq!(table.convert("", ','), pairs(&[("，", Stop)]));
+    assert_eq!(table.convert("", '.'), pairs(&[("．", Stop)]));
+    assert_eq!(table.convert("", '['), pairs(&[("「", Stop)]));
+    assert_eq!(table.convert("z", ','), pairs(&[("‥", Stop)]));
+
+    config.punctuation_style = PunctuationStyle::Ascii;
+    let table = RuleTable::from_config(&config);
+    assert_eq!(table.convert("", ','), pairs(&[(",", Stop)]));
+    assert_eq!(table.convert("", ']'), pairs(&[("]", Stop)]));
+    assert_eq!(table.convert("", '-'), pairs(&[("-", Stop)]));
+
+    config.romaji_table = RomajiTable::Azik;
+    let table = RuleTable::from_config(&config);
+    assert_eq!(table.convert("", '.'), pairs(&[(".", Stop)]));
+    assert_eq!(table.get("["), None);
+  }
+
+  #[test]
+  fn typos() {
+    let table = RuleTable::default_rules();
+    let output = |input| table.get(input).map(|rule| rule.output.clone());
+    assert_eq!(output("zya"), Some("じゃ".to_owned()));
+    assert_eq!(output("sha"), Some("しゃ".to_owned()));
+    assert_eq!(output("vo"), Some("ゔぉ".to_owned()));
+    assert_eq!(output("'"), Some("'".to_owned()));
+  }
+}
@@ -107,6 +107,15 @@ impl Displayable for UnknownWordTransformer {
 
     "[登録: ".to_string() + &self.word.display_string() + "]" + &buf
   }
+
+  // 登録中の語の末尾に入力しているものの中のカーソル
+  fn cursor_offset(&self) -> usize {
+    let len = self.display_string().chars().count();
+    match self.stack.last() {
+      Some(tf) => len - tf.display_string().chars().count() + tf.cursor_offset(),
+      None => len,
+    }
+  }
 }
 
 impl AsTransformerTrait for UnknownWordTransformer {
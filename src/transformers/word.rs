@@ -1,13 +1,18 @@
-use super::tables::BufferPairs;
+use super::tables::{okuri_consonant, BufferPairs, RuleTable};
 use super::Displayable;
 use super::LetterType;
+use std::sync::Arc;
 
 #[derive(Clone, Debug)]
 pub struct YomiPair(BufferPairs, Option<BufferPairs>);
 
 impl YomiPair {
   pub fn new(letter_type: LetterType) -> Self {
-    YomiPair(BufferPairs::new(letter_type), None)
+    Self::new_with_rules(letter_type, RuleTable::default_rules())
+  }
+
+  pub fn new_with_rules(letter_type: LetterType, rules: Arc<RuleTable>) -> Self {
+    YomiPair(BufferPairs::new_with_rules(letter_type, rules), None)
   }
 
   pub fn push(&mut self, character: char) {
@@ -31,16 +36,31 @@ impl YomiPair {
   }
 
   pub fn start_okuri(&mut self) {
-    self.1 = Some(BufferPairs::new(self.0.letter_type()))
+    self.1 = Some(BufferPairs::new_with_rules(
+      self.0.letter_type(),
+      self.0.rules(),
+    ))
   }
 
   pub fn is_empty(&self) -> bool {
     self.0.is_empty()
   }
 
+  pub fn claims(&self, character: char) -> bool {
+    self.1.as_ref().unwrap_or(&self.0).claims(character)
+  }
+
+  pub fn flush(&mut self) {
+    match &mut self.1 {
+      Some(okuri) => okuri.flush(),
+      None => self.0.flush(),
+    }
+  }
+
   pub fn is_stopped(&self) -> bool {
     match &self.1 {
       None => false,
+      Some(okuri) if okuri.rules().is_kana_input() => okuri.has_stopped(),
       Some(yomi) => yomi.is_stopped(),
     }
   }
@@ -67,19 +87,29 @@ impl From<(LetterType, &str)> for YomiPair {
   }
 }
 
+// ▽の中のカーソルより後ろの読みは、表示用と辞書用の組でafter_cursorに持つ
 #[derive(Clone, Debug)]
 pub struct Word {
   pair: YomiPair,
   dic_read: BufferPairs,
   okuri: Option<char>,
+  after_cursor: (BufferPairs, BufferPairs),
 }
 
 impl Word {
   pub fn new(letter_type: LetterType) -> Self {
+    Self::new_with_rules(letter_type, RuleTable::default_rules())
+  }
+
+  pub fn new_with_rules(letter_type: LetterType, rules: Arc<RuleTable>) -> Self {
     Self {
-      pair: YomiPair::new(letter_type),
-      dic_read: BufferPairs::new(LetterType::Hiragana),
+      pair: YomiPair::new_with_rules(letter_type, rules.clone()),
+      dic_read: BufferPairs::new_with_rules(LetterType::Hiragana, rules.clone()),
       okuri: None,
+      after_cursor: (
+        BufferPairs::new_with_rules(letter_type, rules.clone()),
+        BufferPairs::new_with_rules(LetterType::Hiragana, rules),
+      ),
     }
   }
 
@@ -89,13 +119,17 @@ impl Word {
       pair: YomiPair::from((LetterType::Direct, buf)),
       dic_read: BufferPairs::from((LetterType::Direct, buf)),
       okuri: None,
+      after_cursor: (
+        BufferPairs::new(LetterType::Direct),
+        BufferPairs::new(LetterType::Direct),
+      ),
     }
   }
 
   pub fn push(&mut self, character: char) {
     self.try_okuri_start(character);
 
-    let character = character.to_lowercase().next().unwrap();
+    let character = self.normalize(character);
     if self.okuri.is_none() {
       self.dic_read.push(character);
     }
@@ -103,15 +137,36 @@ impl Word {
     self.pair.push(character);
   }
 
+  // かな入力でシフトを押した文字は、小書きのかななどとしてそのまま扱う
+  fn normalize(&self, character: char) -> char {
+    match self.pair.0.rules().is_mapped(character) {
+      true => character,
+      false => character.to_lowercase().next().unwrap(),
+    }
+  }
+
   fn try_okuri_start(&mut self, character: char) {
+    // かな入力では、濁点を待っている読みを確定してから送り仮名を始める
+    if self.is_okuri_key(character) && self.pair.0.rules().is_kana_input() {
+      self.pair.0.flush();
+      self.dic_read.flush();
+    }
     if self.is_okuri_start(character) {
+      // 送り仮名は読みの末尾に付ける
+      self.move_cursor_end();
       self.pair.start_okuri();
       self.okuri = Some(character.to_lowercase().next().unwrap());
     }
   }
 
   fn is_okuri_start(&self, character: char) -> bool {
-    self.pair.0.is_stopped() && self.okuri.is_none() && character.is_ascii_uppercase()
+    self.pair.0.is_stopped() && self.is_okuri_key(character)
+  }
+
+  fn is_okuri_key(&self, character: char) -> bool {
+    self.okuri.is_none()
+      && character.is_ascii_uppercase()
+      && !self.pair.0.rules().is_mapped(character)
   }
 
   pub fn pop(&mut self) {
@@ -122,6 +177,55 @@ impl Word {
     }
   }
 
+  // 送り仮名や入力途中の列があるときはカーソルを動かさない
+  // 表示用と辞書用の読みがずれないよう、両方を動かせるときだけ動かす
+  pub fn move_cursor_left(&mut self) -> bool {
+    if self.okuri.is_some() {
+      return false;
+    }
+    if !self.pair.0.is_stopped() || !self.dic_read.is_stopped() {
+      return false;
+    }
+
+    self.pair.0.move_last_to(&mut self.after_cursor.0);
+    self.dic_read.move_last_to(&mut self.after_cursor.1);
+    true
+  }
+
+  pub fn move_cursor_right(&mut self) -> bool {
+    if self.okuri.is_some() {
+      return false;
+    }
+    if self.has_pending() {
+      return false;
+    }
+    if self.after_cursor.0.is_empty() || self.after_cursor.1.is_empty() {
+      return false;
+    }
+
+    self.after_cursor.0.move_first_to(&mut self.pair.0);
+    self.after_cursor.1.move_first_to(&mut self.dic_read);
+    true
+  }
+
+  fn has_pending(&self) -> bool {
+    !self.pair.0.is_empty() && !self.pair.0.is_stopped()
+      || !self.dic_read.is_empty() && !self.dic_read.is_stopped()
+  }
+
+  // カーソルより後ろにある読みの文字数
+  pub fn after_cursor_len(&self) -> usize {
+    self.after_cursor.0.to_string().chars().count()
+  }
+
+  pub fn move_cursor_home(&mut self) {
+    while self.move_cursor_left() {}
+  }
+
+  pub fn move_cursor_end(&mut self) {
+    while self.move_cursor_right() {}
+  }
+
   pub fn remove_okuri(&mut self) {
     self.pair.1 = None;
     self.okuri = None;
@@ -136,26 +240,48 @@ impl Word {
   }
 
   pub fn is_empty(&self) -> bool {
-    self.pair.is_empty()
+    self.pair.is_empty() && self.after_cursor.0.is_empty()
   }
 
   pub fn is_stopped(&self) -> bool {
     self.pair.is_stopped()
   }
 
+  pub fn claims(&self, character: char) -> bool {
+    self.pair.claims(character)
+  }
+
+  pub fn flush(&mut self) {
+    self.pair.flush();
+    if self.okuri.is_none() {
+      self.dic_read.flush();
+    }
+  }
+
   pub fn to_dic_read(&self) -> Option<String> {
-    if self.dic_read.is_empty() {
+    if self.dic_read.is_empty() && self.after_cursor.1.is_empty() {
       return None;
     }
 
-    let read =
-      self.dic_read.to_string() + &self.okuri.map(|c| c.to_string()).unwrap_or("".to_owned());
+    // かな入力では、送り仮名の先頭のかなから子音を求める
+    let okuri = match self.dic_read.rules().is_kana_input() {
+      true => self
+        .okuri_string()
+        .and_then(|okuri| okuri_consonant(okuri.chars().next()?)),
+      false => self.okuri,
+    };
+    let read = self.dic_read.to_string()
+      + &self.after_cursor.1.to_string()
+      + &okuri.map(|c| c.to_string()).unwrap_or("".to_owned());
 
     Some(read)
   }
 
   fn to_string_pair(&self) -> (String, Option<String>) {
-    (self.pair.yomi_string(), self.pair.okuri_string())
+    (
+      self.pair.yomi_string() + &self.after_cursor.0.to_string(),
+      self.pair.okuri_string(),
+    )
   }
 }
 
@@ -290,4 +416,33 @@ mod tests {
       assert_eq!(word.to_string_pair(), ("".to_owned(), None));
     }
   }
+
+  #[test]
+  fn move_cursor() {
+    let mut word = Word::from((Hiragana, "kanji"));
+    assert!(word.move_cursor_left());
+    assert_eq!(word.after_cursor_len(), 1);
+    assert_eq!(word.to_dic_read(), Some("かんじ".to_owned()));
+    assert!(word.move_cursor_right());
+    assert!(!word.move_cursor_right());
+    assert_eq!(word.after_cursor_len(), 0);
+
+    // 辞書用の読みを動かせないときは、表示用の読みも動かさない
+    let mut word = Word::from((Hiragana, "kanji"));
+    word.dic_read = BufferPairs::new(Hiragana);
+    assert!(!word.move_cursor_left());
+    assert_eq!(word.after_cursor_len(), 0);
+    assert_eq!(&word.display_string(), "かんじ");
+
+    // 入力途中の列は捨てずに、カーソルを動かさない
+    let mut word = Word::from((Hiragana, "kan"));
+    assert!(!word.move_cursor_left());
+    assert_eq!(&word.display_string(), "かn");
+    word.flush();
+    assert!(word.move_cursor_left());
+    word.push('k');
+    assert!(!word.move_cursor_right());
+    assert_eq!(&word.display_string(), "かkん");
+    assert_eq!(word.after_cursor_len(), 1);
+  }
 }
@@ -1,10 +1,11 @@
 use kana::{half2kana, hira2kata, kata2hira};
 
-use super::tables::LetterType;
+use super::tables::{to_en_katakana, LetterType};
 use super::{
   AsTransformerTrait, Displayable, KeyCode, SelectCandidateTransformer, Stackable, Transformable,
   TransformerTypes, UnknownWordTransformer, WithContext, Word,
 };
+use crate::keyboards::Keyboard;
 use crate::Context;
 
 #[derive(Clone, Debug)]
@@ -16,16 +17,18 @@ pub struct YomiTransformer {
 
 impl YomiTransformer {
   pub fn new(context: Context, transformer_type: TransformerTypes) -> Self {
+    let letter_type = match transformer_type {
+      TransformerTypes::Hiragana => LetterType::Hiragana,
+      TransformerTypes::Katakana => LetterType::Katakana,
+      TransformerTypes::EnKatakana => LetterType::EnKatakana,
+      _ => unreachable!(),
+    };
+    let rule_table = context.rule_table();
+
     YomiTransformer {
       context,
       current_transformer_type: transformer_type,
-      word: Word::new(match transformer_type {
-        TransformerTypes::Hiragana => LetterType::Hiragana,
-        TransformerTypes::Katakana => LetterType::Katakana,
-        TransformerTypes::EnKatakana => unimplemented!(),
-        TransformerTypes::EmEisu => unimplemented!(),
-        _ => unreachable!(),
-      }),
+      word: Word::new_with_rules(letter_type, rule_table),
     }
   }
 
@@ -39,13 +42,35 @@ impl YomiTransformer {
   fn try_transition_to_select_candidate(&self) -> Option<SelectCandidateTransformer> {
     self
       .context
-      .dictionary()
-      .transform(self.word.to_dic_read()?)
-      .map(|dic_entry| {
-        SelectCandidateTransformer::new(self.new_context(), dic_entry, self.word.clone())
+      .lookup(&self.word.to_dic_read()?)
+      .map(|(dic_entry, dynamic_candidates)| {
+        SelectCandidateTransformer::new(
+          self.new_context(),
+          &dic_entry,
+          &dynamic_candidates,
+          self.word.clone(),
+        )
       })
   }
 
+  // 「kan」の「n」のような入力途中の列を、変換や確定の前に出力する
+  fn flushed(&self) -> Self {
+    let mut tf = self.clone();
+    if self.context.config().is_flush_pending_input() {
+      tf.word.flush();
+    }
+
+    tf
+  }
+
+  // 入力途中の列は、カーソルを動かす前に出力する
+  fn move_cursor(&self, f: fn(&mut Word)) -> Option<Vec<Box<dyn Transformable>>> {
+    let mut tf = self.flushed();
+    f(&mut tf.word);
+
+    Some(vec![box tf])
+  }
+
   fn transition_to_unknown_word(&self) -> UnknownWordTransformer {
     UnknownWordTransformer::new(self.new_context(), { self.word.clone() })
   }
@@ -70,6 +95,26 @@ impl Transformable for YomiTransformer {
     TransformerTypes::Yomi
   }
 
+  // 半角カタカナへの切り替えキーでは、読みを半角カタカナにして確定する
+  fn try_change_transformer(
+    &self,
+    keyboard: &Box<dyn Keyboard>,
+    _: &KeyCode,
+  ) -> Option<Box<dyn Transformable>> {
+    match self
+      .context
+      .config()
+      .key_config()
+      .enter_en_katakana_transformer
+      .fulfilled(keyboard.pressing_keys())
+    {
+      true => {
+        Some(self.to_completed_with_update_buffer(to_en_katakana(&self.flushed().buffer_content())))
+      }
+      false => None,
+    }
+  }
+
   fn push_character(&self, character: char) -> Option<Vec<Box<dyn Transformable>>> {
     let mut tf = self.clone();
     tf.word.push(character);
@@ -93,14 +138,15 @@ impl Transformable for YomiTransformer {
   }
 
   fn push_space(&self) -> Option<Vec<Box<dyn Transformable>>> {
-    let mut tf = self.clone();
+    let yomi = self.flushed();
+    let mut tf = yomi.clone();
     tf.word.remove_okuri();
 
-    Some(vec![box tf, self.try_composition()])
+    Some(vec![box tf, yomi.try_composition()])
   }
 
   fn push_enter(&self) -> Option<Vec<Box<dyn Transformable>>> {
-    let mut tf = self.clone();
+    let mut tf = self.flushed();
     tf.word.remove_okuri();
 
     Some(vec![
@@ -123,18 +169,41 @@ impl Transformable for YomiTransformer {
     self.push_backspace()
   }
 
+  fn push_arrow_left(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    self.move_cursor(|word| {
+      word.move_cursor_left();
+    })
+  }
+
+  fn push_arrow_right(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    self.move_cursor(|word| {
+      word.move_cursor_right();
+    })
+  }
+
+  fn push_home(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    self.move_cursor(Word::move_cursor_home)
+  }
+
+  fn push_end(&self) -> Option<Vec<Box<dyn Transformable>>> {
+    self.move_cursor(Word::move_cursor_end)
+  }
+
   fn push_any_character(&self, key: &KeyCode) -> Option<Vec<Box<dyn Transformable>>> {
-    match key.printable_key() {
-      Some('q') => Some(vec![self.to_completed_with_update_buffer(
-        match self.current_transformer_type {
-          TransformerTypes::Hiragana => hira2kata(&self.buffer_content()),
-          TransformerTypes::Katakana => kata2hira(&self.buffer_content()),
-          TransformerTypes::EnKatakana => half2kana(&self.buffer_content()),
-          _ => return None,
-        },
-      )]),
-      _ => None,
+    let toggle_kana = self.context.config().romaji_table.toggle_kana_character();
+    if key.printable_key() != Some(toggle_kana) || self.word.claims(toggle_kana) {
+      return None;
     }
+
+    let buffer = self.flushed().buffer_content();
+    Some(vec![self.to_completed_with_update_buffer(
+      match self.current_transformer_type {
+        TransformerTypes::Hiragana => hira2kata(&buffer),
+        TransformerTypes::Katakana => kata2hira(&buffer),
+        TransformerTypes::EnKatakana => half2kana(&buffer),
+        _ => return None,
+      },
+    )])
   }
 }
 
@@ -146,6 +215,10 @@ impl Displayable for YomiTransformer {
   fn display_string(&self) -> String {
     "▽".to_owned() + &self.word.display_string()
   }
+
+  fn cursor_offset(&self) -> usize {
+    self.display_string().chars().count() - self.word.after_cursor_len()
+  }
 }
 
 impl AsTransformerTrait for YomiTransformer {
@@ -184,7 +257,7 @@ impl Stackable for YomiTransformer {
 
 #[cfg(test)]
 mod tests {
-  use crate::tests::dummy_context;
+  use crate::tests::{dummy_context, dummy_context_with};
   use crate::transformers::StoppedReason::*;
   use crate::transformers::TransformerTypes::*;
 
@@ -220,9 +293,112 @@ mod tests {
       ["aK[backspace][backspace]K", { display: "▽k", transformer_type: Yomi }],
       ["henka[backspace][backspace]", { display: "▽へ", transformer_type: Yomi }],
       ["katakanaq", { stopped_buffer: "カタカナ", transformer_type: Stopped(Compleated) }],
+      ["katakana[down:ctrl]q", { stopped_buffer: "ｶﾀｶﾅ", transformer_type: Stopped(Compleated) }],
+      ["kanq", { stopped_buffer: "カン", transformer_type: Stopped(Compleated) }],
+      ["kan[down:ctrl]q", { stopped_buffer: "ｶﾝ", transformer_type: Stopped(Compleated) }],
+    ];
+    crate::tests::helpers::TestData::batch(vec);
+  }
+
+  #[test]
+  fn cursor() {
+    let conf = dummy_context();
+
+    let vec = crate::tds![conf, YomiTransformer, Hiragana;
+      ["kanji[home]", { display: "▽かんじ", transformer_type: Yomi }],
+      ["anji[home]ka", { display: "▽かあんじ", transformer_type: Yomi }],
+      ["anji[home]ka ", { display: "[登録: かあんじ]", transformer_type: UnknownWord }],
+      ["kaji[arrow_left]nn ", { display: "▼漢字", transformer_type: SelectCandidate }],
+      ["kanji[arrow_left][arrow_left][backspace]", { display: "▽んじ", transformer_type: Yomi }],
+      ["kanji[home][backspace]", { display: "▽かんじ", transformer_type: Yomi }],
+      ["kanji[home][arrow_right]k[end]i", { display: "▽かきんじ", transformer_type: Yomi }],
+      ["kan[arrow_left]ji", { display: "▽かじん", transformer_type: Yomi }],
+      ["kan[home]ji", { display: "▽じかん", transformer_type: Yomi }],
+      ["kan[home][end]ji", { display: "▽かんじ", transformer_type: Yomi }],
+      ["kanj[arrow_left]i", { display: "▽かんじ", transformer_type: Yomi }],
+      ["kanji[arrow_left]k[arrow_right]a", { display: "▽かんかじ", transformer_type: Yomi }],
+      ["kanji[home][end]Ta", { display: "[登録: かんじ*た]", transformer_type: UnknownWord }],
+      ["kanji[home]\n", { stopped_buffer: "かんじ", transformer_type: Stopped(Compleated) }],
     ];
     crate::tests::helpers::TestData::batch(vec);
 
     // TODO: カタカナ時のテスト
   }
+
+  #[test]
+  fn n_and_sokuon() {
+    let conf = dummy_context();
+
+    let vec = crate::tds![conf, YomiTransformer, Hiragana;
+      ["kanji", { display: "▽かんじ", transformer_type: Yomi }],
+      ["kani", { display: "▽かに", transformer_type: Yomi }],
+      ["kan'i", { display: "▽かんい", transformer_type: Yomi }],
+      ["n'a", { display: "▽んあ", transformer_type: Yomi }],
+      ["nna", { display: "▽んあ", transformer_type: Yomi }],
+      ["nka", { display: "▽んか", transformer_type: Yomi }],
+      ["nya", { display: "▽にゃ", transformer_type: Yomi }],
+      ["n,", { display: "▽ん、", transformer_type: Yomi }],
+      ["kan", { display: "▽かn", transformer_type: Yomi }],
+      ["kan\n", { stopped_buffer: "かん", transformer_type: Stopped(Compleated) }],
+      ["kan ", { display: "[登録: かん]", transformer_type: UnknownWord }],
+      ["kannji ", { display: "▼漢字", transformer_type: SelectCandidate }],
+      ["kanjin\n", { stopped_buffer: "かんじん", transformer_type: Stopped(Compleated) }],
+    ];
+    crate::tests::helpers::TestData::batch(vec);
+
+    let tf = crate::tf!(conf, YomiTransformer, Hiragana);
+    let rules = conf.rule_table();
+    let vec = "wrtypsdghjkzxcvbmf"
+      .chars()
+      .flat_map(|c| "aiueo".chars().map(move |v| (c, v)))
+      .filter_map(|(c, v)| {
+        let output = &rules.get(&format!("{}{}", c, v))?.output;
+        Some(crate::tests::helpers::TestData::new(
+          format!("{}{}{}", c, c, v),
+          tf.clone(),
+          crate::td!({ display: format!("▽っ{}", output), transformer_type: Yomi }),
+        ))
+      })
+      .collect::<Vec<_>>();
+    assert!(vec.len() > 18 * 4);
+    crate::tests::helpers::TestData::batch(vec);
+  }
+
+  #[test]
+  fn without_trailing_n_flush() {
+    let mut config = crate::RSKKConfig::default_config();
+    config.is_enable_trailing_n_flush = false;
+    let conf = dummy_context_with(config);
+
+    let vec = crate::tds![conf, YomiTransformer, Hiragana;
+      ["kanji", { display: "▽かんじ", transformer_type: Yomi }],
+      ["kan\n", { stopped_buffer: "かn", transformer_type: Stopped(Compleated) }],
+      ["kan ", { display: "[登録: かn]", transformer_type: UnknownWord }],
+      ["kan[arrow_left]ji", { display: "▽かんじ", transformer_type: Yomi }],
+      ["kan[home]a", { display: "▽かな", transformer_type: Yomi }],
+    ];
+    crate::tests::helpers::TestData::batch(vec);
+  }
+
+  #[test]
+  fn kana_input() {
+    let mut config = crate::RSKKConfig::default_config();
+    config.input_method = crate::transformers::InputMethod::Kana;
+    let conf = dummy_context_with(config);
+
+    let vec = crate::tds![conf, YomiTransformer, Hiragana;
+      ["t", { display: "▽か", transformer_type: Yomi }],
+      ["t[[]", { display: "▽が", transformer_type: Yomi }],
+      ["3E", { display: "▽あぃ", transformer_type: Yomi }],
+      ["tyd[[] ", { display: "▼漢字", transformer_type: SelectCandidate }],
+      ["tyd ", { display: "[登録: かんし]", transformer_type: UnknownWord }],
+      ["tq\n", { stopped_buffer: "かた", transformer_type: Stopped(Compleated) }],
+      ["6hR", { display: "▽おく*す", transformer_type: Yomi }],
+      ["6hR[[]", { display: "[登録: おく*ず]", transformer_type: UnknownWord }],
+      ["6hL", { display: "▼送り", transformer_type: SelectCandidate }],
+      ["6hL\n", { stopped_buffer: "送り", transformer_type: Stopped(Compleated) }],
+      ["6hR ", { display: "[登録: おく*す]", transformer_type: UnknownWord }],
+    ];
+    crate::tests::helpers::TestData::batch(vec);
+  }
 }